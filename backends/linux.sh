@@ -2,7 +2,11 @@
 set -euo pipefail
 
 query="$1"
-[[ -z "$query" ]] && exit 0
+limit="${LAUNCHER_LIMIT:-50}"
+in_dir="${LAUNCHER_IN:-}"
+apps_only="${LAUNCHER_APPS_ONLY:-}"
+IFS=',' read -r -a exts <<< "${LAUNCHER_EXT:-}"
+[[ -z "$query" && ${#exts[@]} -eq 0 && -z "$in_dir" && -z "$apps_only" ]] && exit 0
 
 cache_dir="$HOME/.cache/qol-launcher-dbs"
 mkdir -p "$cache_dir"
@@ -14,27 +18,59 @@ for dir in /media/*/; do
     [[ -f "$db_path" ]] && db_args+=("-d" "$db_path")
 done
 
+# Every pattern must match: plocate ANDs them.
 search_plocate() {
-    local pattern="$1" limit="$2"
-    { [[ ${#db_args[@]} -gt 0 ]] && plocate -i -l "$limit" "${db_args[@]}" "$pattern" 2>/dev/null || true
-      plocate -i -l "$limit" "$pattern" 2>/dev/null || true
-    } | { grep -v -E "^/timeshift/|/app-install/|^/mnt/" || true; } | awk '!seen[$0]++'
+    local limit="$1"
+    shift
+    { [[ ${#db_args[@]} -gt 0 ]] && plocate -i -l "$limit" "${db_args[@]}" "$@" 2>/dev/null || true
+      plocate -i -l "$limit" "$@" 2>/dev/null || true
+    } | awk '!seen[$0]++'
 }
 
-search_desktop_dirs() {
-    local dirs=(
-        "/usr/share/applications"
-        "/usr/lib"
-        "$HOME/.local/share/applications"
-        "/var/lib/flatpak/exports/share/applications"
-    )
-    for d in "${dirs[@]}"; do
-        find "$d" -maxdepth 3 -iname "*${query}*.desktop" 2>/dev/null || true
+# Each word is its own pattern, so words can match the name or any folder in any
+# order. The extension and $LAUNCHER_IN narrow the search further.
+search_files() {
+    local ext="$1"
+    shift
+    local patterns=()
+    [[ -n "$in_dir" ]] && patterns+=("${in_dir%/}/*")
+    for word in "$@"; do
+        patterns+=("*${word}*")
     done
+    [[ -n "$ext" ]] && patterns+=("*.${ext}")
+    search_plocate "$limit" "${patterns[@]}"
 }
 
-{
-    search_desktop_dirs
-    search_plocate "*${query}*.desktop" 30
-    search_plocate "*$query*" 200
-} | awk '!seen[$0]++' | head -n 50
+# plocate compares bytes, so `café` would miss decomposed names and `cafe`. Words
+# with non-ASCII letters are searched again with those letters as wildcards, unless
+# a word has no ASCII letters left to search for.
+ascii_words() {
+    local word
+    for word in "${words[@]}"; do
+        printf '%s' "$word" | LC_ALL=C sed 's/[^ -~]\{1,\}/*/g'
+        echo
+    done
+}
+
+search_all() {
+    local ext="$1" word
+    search_files "$ext" "${words[@]}"
+    [[ "${loose[*]}" == "${words[*]}" ]] && return
+    for word in "${loose[@]}"; do
+        [[ "$word" == *[[:alnum:]]* ]] || return 0
+    done
+    search_files "$ext" "${loose[@]}"
+}
+
+# Applications come from the launcher's own desktop entry index.
+[[ -n "$apps_only" ]] && exit 0
+
+read -r -a words <<< "$query"
+mapfile -t loose < <(ascii_words)
+if [[ ${#exts[@]} -eq 0 ]]; then
+    search_all ""
+else
+    for ext in "${exts[@]}"; do
+        search_all "$ext"
+    done
+fi | awk '!seen[$0]++' | head -n "$limit"
@@ -2,10 +2,18 @@
 set -euo pipefail
 
 query="$1"
-limit=50
+limit="${LAUNCHER_LIMIT:-50}"
+exts="${LAUNCHER_EXT:-}"
+ext="${exts%%,*}"
 
-if [[ -z "$query" ]]; then
+if [[ -z "$query" && -z "$ext" ]]; then
     exit 0
 fi
 
-mdfind -name "$query" 2>/dev/null | head -n "$limit"
+# Other extensions are filtered by the launcher; mdfind only gets the first.
+name="${query:-.$ext}"
+if [[ -n "${LAUNCHER_IN:-}" ]]; then
+    mdfind -onlyin "$LAUNCHER_IN" -name "$name" 2>/dev/null | head -n "$limit"
+else
+    mdfind -name "$name" 2>/dev/null | head -n "$limit"
+fi
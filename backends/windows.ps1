@@ -1,15 +1,19 @@
 param([string]$query)
 
-if ([string]::IsNullOrEmpty($query)) {
+if ([string]::IsNullOrEmpty($query) -and -not $env:LAUNCHER_EXT -and -not $env:LAUNCHER_IN) {
     exit 0
 }
 
-$limit = 50
+$limit = if ($env:LAUNCHER_LIMIT) { [int]$env:LAUNCHER_LIMIT } else { 50 }
 
 # Try Everything CLI first (faster, if installed)
 $es = Get-Command "es.exe" -ErrorAction SilentlyContinue
 if ($es) {
-    & es.exe -n $limit -i $query 2>$null
+    $esArgs = @('-n', $limit, '-i')
+    if ($env:LAUNCHER_IN) { $esArgs += @('-path', $env:LAUNCHER_IN) }
+    if ($env:LAUNCHER_EXT) { $esArgs += "ext:$($env:LAUNCHER_EXT -replace ',', ';')" }
+    if ($query) { $esArgs += $query }
+    & es.exe @esArgs 2>$null
     exit 0
 }
 
@@ -4,10 +4,18 @@ set -e
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 BINARY="$SCRIPT_DIR/launcher"
 
+# The hotkey toggles the window; explicit --show, --query or --dmenu runs are passed through as given.
+args=(--toggle "$@")
+for arg in "$@"; do
+    case "$arg" in
+        --show|--query|--dmenu) args=("$@"); break ;;
+    esac
+done
+
 if [[ -x "$BINARY" ]]; then
-    exec "$BINARY"
+    exec "$BINARY" "${args[@]}"
 elif command -v launcher &> /dev/null; then
-    exec launcher
+    exec launcher "${args[@]}"
 else
     echo "launcher binary not found" >&2
     echo "Install from: https://github.com/qol-tools/plugin-launcher/releases" >&2
@@ -0,0 +1,180 @@
+//! Installed applications, found without the search backends. Every desktop entry in
+//! [`desktop::application_dirs`] is loaded once, deduplicated by desktop ID, and kept
+//! in memory; the index reloads when one of those directories changes.
+
+use crate::desktop::{self, DesktopEntry};
+use crate::normalize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::SystemTime;
+
+struct Index {
+    dirs: Vec<PathBuf>,
+    /// Modification times of the directories and their subdirectories at load time.
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+    entries: Arc<Vec<DesktopEntry>>,
+}
+
+/// Adding, removing or renaming an entry changes its directory's modification time.
+fn collect_stamps(dir: &Path, out: &mut Vec<(PathBuf, Option<SystemTime>)>) {
+    out.push((dir.to_path_buf(), fs::metadata(dir).and_then(|m| m.modified()).ok()));
+    let Ok(read) = fs::read_dir(dir) else { return };
+    let mut subdirs: Vec<PathBuf> = read.filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .collect();
+    subdirs.sort();
+    for subdir in subdirs {
+        collect_stamps(&subdir, out);
+    }
+}
+
+fn stamps(dirs: &[PathBuf]) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut out = Vec::new();
+    for dir in dirs {
+        collect_stamps(dir, &mut out);
+    }
+    out
+}
+
+impl Index {
+    fn load(dirs: Vec<PathBuf>) -> Self {
+        let stamps = stamps(&dirs);
+        let entries = Arc::new(desktop::load_desktop_entries(&dirs));
+        Index { dirs, stamps, entries }
+    }
+
+    /// Reloads when a directory changed since the last load; returns whether it did.
+    fn refresh(&mut self) -> bool {
+        if stamps(&self.dirs) == self.stamps {
+            return false;
+        }
+        *self = Index::load(std::mem::take(&mut self.dirs));
+        true
+    }
+}
+
+/// Current application entries, loading them on first use.
+pub fn entries() -> Arc<Vec<DesktopEntry>> {
+    static INDEX: OnceLock<Mutex<Index>> = OnceLock::new();
+    let mut index = INDEX.get_or_init(|| Mutex::new(Index::load(desktop::application_dirs())))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    index.refresh();
+    index.entries.clone()
+}
+
+/// Whether every word of `query` occurs in the entry's name, one of its keywords,
+/// the program it runs or its desktop ID. Entries marked `NoDisplay` never match.
+pub fn matches(entry: &DesktopEntry, query: &str, transliterate: bool) -> bool {
+    if entry.no_display {
+        return false;
+    }
+    let fields: Vec<String> = [entry.name.as_str(), entry.id.trim_end_matches(".desktop")].into_iter()
+        .chain(entry.keywords.iter().map(String::as_str))
+        .map(str::to_string)
+        .chain(desktop::exec_program(entry))
+        .map(|field| normalize::fold_with(&field, transliterate))
+        .collect();
+    normalize::fold_with(query, transliterate)
+        .split_whitespace()
+        .all(|word| fields.iter().any(|field| field.contains(word)))
+}
+
+/// Applications matching `query`, in precedence order.
+pub fn search(query: &str, transliterate: bool) -> Vec<DesktopEntry> {
+    entries().iter().filter(|e| matches(e, query, transliterate)).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn app(name: &str, exec: &str, keywords: &[&str]) -> DesktopEntry {
+        DesktopEntry {
+            id: "org.example.App.desktop".to_string(),
+            name: name.to_string(),
+            exec: exec.to_string(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    mod matches {
+        use super::*;
+
+        #[test]
+        fn finds_by_name_keyword_or_program() {
+            // Arrange
+            let e = app("Files", "/usr/bin/nautilus --new-window", &["folder", "explorer"]);
+
+            // Assert
+            assert!(matches(&e, "fil", false));
+            assert!(matches(&e, "Explorer", false));
+            assert!(matches(&e, "nautilus", false));
+            assert!(matches(&e, "example", false));
+            assert!(!matches(&e, "browser", false));
+        }
+
+        #[test]
+        fn words_may_match_different_fields() {
+            let e = app("Files", "nautilus", &["folder"]);
+            assert!(matches(&e, "folder naut", false));
+            assert!(!matches(&e, "folder web", false));
+        }
+
+        #[test]
+        fn folds_accents() {
+            let e = app("Éditeur", "editeur", &[]);
+            assert!(matches(&e, "edit", false));
+        }
+
+        #[test]
+        fn skips_no_display_entries() {
+            let e = DesktopEntry { no_display: true, ..app("Files", "nautilus", &[]) };
+            assert!(!matches(&e, "files", false));
+        }
+    }
+
+    mod refresh {
+        use super::*;
+
+        fn write(dir: &Path, name: &str) {
+            fs::write(dir.join(name), format!("[Desktop Entry]\nType=Application\nName={}\nExec=x\n", name)).unwrap();
+        }
+
+        #[test]
+        fn keeps_index_while_unchanged() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            write(dir.path(), "a.desktop");
+            let mut index = Index::load(vec![dir.path().to_path_buf()]);
+
+            // Act
+            let reloaded = index.refresh();
+
+            // Assert
+            assert!(!reloaded);
+            assert_eq!(index.entries.len(), 1);
+        }
+
+        #[test]
+        fn reloads_after_install_in_subdirectory() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let sub = dir.path().join("kde4");
+            fs::create_dir(&sub).unwrap();
+            let mut index = Index::load(vec![dir.path().to_path_buf()]);
+
+            // Act
+            write(&sub, "b.desktop");
+            let reloaded = index.refresh();
+
+            // Assert
+            assert!(reloaded);
+            assert_eq!(index.entries[0].id, "kde4-b.desktop");
+        }
+    }
+}
@@ -0,0 +1,1196 @@
+//! `config.json`: loaded once, validated field by field and reloaded when the file changes.
+//!
+//! Files carry a `version`; older files are brought up to date by [`MIGRATIONS`]
+//! before parsing, and every field has a default so new fields never break old files.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Bump together with a new entry in [`MIGRATIONS`] when the file format changes.
+pub const CONFIG_VERSION: u64 = 1;
+
+/// `MIGRATIONS[n]` turns a version `n` file into version `n + 1`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); CONFIG_VERSION as usize] = [migrate_v0_to_v1];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    pub version: u64,
+    pub half_life_days: f64,
+    pub frequency_bonus: i32,
+    pub prefer_apps: bool,
+    pub penalize_hidden: bool,
+    pub depth_penalty: i32,
+    pub exact_bonus: i32,
+    pub prefix_penalty: i32,
+    pub contains_penalty: i32,
+    /// Match names in other scripts by their Latin spelling ("moskva" matches "Москва").
+    /// Affects ranking, applications and dmenu; the file backends still need the name as written.
+    pub transliterate: bool,
+    /// Profile applied to queries without an `@name` prefix; empty means none.
+    pub profile: String,
+    pub profiles: BTreeMap<String, Profile>,
+    pub terminal: TerminalConfig,
+    pub actions: Vec<CustomAction>,
+    pub scope: ScopeConfig,
+}
+
+/// Which paths searches may return and which roots rank higher; see `crate::scope`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ScopeConfig {
+    /// Only show files under these roots; empty means everywhere. Applications are always shown.
+    pub include: Vec<String>,
+    /// Glob patterns; without a leading `/` or `~` they match at any depth.
+    pub exclude: Vec<String>,
+    /// Score bonus for paths under a root; the deepest matching root counts.
+    pub boosts: BTreeMap<String, i32>,
+    /// What to do with paths a git working tree ignores.
+    pub gitignored: GitIgnored,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GitIgnored {
+    Show,
+    #[default]
+    Penalize,
+    Hide,
+}
+
+impl Default for ScopeConfig {
+    fn default() -> Self {
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect();
+        Self {
+            include: Vec::new(),
+            exclude: strings(&["/timeshift", "/mnt", "app-install", ".git", "node_modules", "target", "__pycache__"]),
+            boosts: ["/usr/share/applications", "/usr/lib", "~/.local/share/applications"]
+                .iter()
+                .map(|root| (root.to_string(), 50))
+                .collect(),
+            gitignored: GitIgnored::default(),
+        }
+    }
+}
+
+/// Named ranking weights, picked with an `@name` query prefix. Unset weights keep
+/// the top-level values.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_bonus: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefer_apps: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub penalize_hidden: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth_penalty: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exact_bonus: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix_penalty: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains_penalty: Option<i32>,
+}
+
+impl Profile {
+    /// Every weight of `config`, as written by `launcher --tune`.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            frequency_bonus: Some(config.frequency_bonus),
+            prefer_apps: Some(config.prefer_apps),
+            penalize_hidden: Some(config.penalize_hidden),
+            depth_penalty: Some(config.depth_penalty),
+            exact_bonus: Some(config.exact_bonus),
+            prefix_penalty: Some(config.prefix_penalty),
+            contains_penalty: Some(config.contains_penalty),
+        }
+    }
+
+    pub fn apply(&self, config: &Config) -> Config {
+        Config {
+            frequency_bonus: self.frequency_bonus.unwrap_or(config.frequency_bonus),
+            prefer_apps: self.prefer_apps.unwrap_or(config.prefer_apps),
+            penalize_hidden: self.penalize_hidden.unwrap_or(config.penalize_hidden),
+            depth_penalty: self.depth_penalty.unwrap_or(config.depth_penalty),
+            exact_bonus: self.exact_bonus.unwrap_or(config.exact_bonus),
+            prefix_penalty: self.prefix_penalty.unwrap_or(config.prefix_penalty),
+            contains_penalty: self.contains_penalty.unwrap_or(config.contains_penalty),
+            ..config.clone()
+        }
+    }
+}
+
+fn default_profiles() -> BTreeMap<String, Profile> {
+    BTreeMap::from([
+        ("apps-first".to_string(), Profile {
+            prefer_apps: Some(true),
+            frequency_bonus: Some(800),
+            ..Profile::default()
+        }),
+        ("code".to_string(), Profile {
+            prefer_apps: Some(false),
+            penalize_hidden: Some(false),
+            depth_penalty: Some(0),
+            ..Profile::default()
+        }),
+        ("documents".to_string(), Profile {
+            prefer_apps: Some(false),
+            depth_penalty: Some(1),
+            contains_penalty: Some(150),
+            ..Profile::default()
+        }),
+    ])
+}
+
+impl Config {
+    /// The weights for `query` and the query without its profile prefix. `@name rest`
+    /// picks that profile; otherwise the default [`Config::profile`] applies, if any.
+    pub fn for_query<'q>(&self, query: &'q str) -> (Config, &'q str) {
+        let trimmed = query.trim_start();
+        if let Some(rest) = trimmed.strip_prefix('@') {
+            let (name, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            if let Some(profile) = self.profiles.get(name) {
+                return (profile.apply(self), rest.trim_start());
+            }
+        }
+        match self.profiles.get(&self.profile) {
+            Some(profile) => (profile.apply(self), query),
+            None => (self.clone(), query),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// Command template with `{dir}` and `{cmd}` placeholders. Empty means auto-detect.
+    pub command: String,
+}
+
+/// A user-defined action. `command` is split into arguments before `{path}`, `{dir}`
+/// and `{name}` are substituted, so a filename stays one argument. A command that hands
+/// a placeholder to a shell, as in `sh -c 'echo {path}'`, still has it parsed there.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CustomAction {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub key: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub mime_types: Vec<String>,
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            half_life_days: 7.0,
+            frequency_bonus: 500,
+            prefer_apps: true,
+            penalize_hidden: true,
+            depth_penalty: 2,
+            exact_bonus: 0,
+            prefix_penalty: 100,
+            contains_penalty: 200,
+            transliterate: false,
+            profile: String::new(),
+            profiles: default_profiles(),
+            terminal: TerminalConfig::default(),
+            actions: Vec::new(),
+            scope: ScopeConfig::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem with one field; `field` is empty when the whole file is affected.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub message: String,
+    pub severity: Severity,
+}
+
+impl ConfigIssue {
+    fn error(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into(), severity: Severity::Error }
+    }
+
+    fn warning(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into(), severity: Severity::Warning }
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.field.as_str() {
+            "" => write!(f, "{}", self.message),
+            field => write!(f, "{}: {}", field, self.message),
+        }
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("qol-tray/plugins/plugin-launcher/config.json")
+}
+
+/// Files written before `version` existed already match version 1.
+fn migrate_v0_to_v1(_config: &mut Map<String, Value>) {}
+
+/// Runs the migrations the file still needs and stamps it with [`CONFIG_VERSION`].
+/// A file from a newer launcher is left alone; its known fields are still used.
+pub fn migrate(config: &mut Map<String, Value>) -> Option<ConfigIssue> {
+    let version = match config.get("version") {
+        None => 0,
+        Some(value) => match value.as_u64() {
+            Some(version) => version,
+            None => {
+                config.insert("version".to_string(), json!(CONFIG_VERSION));
+                return Some(ConfigIssue::error(
+                    "version",
+                    format!("must be a whole number; assuming {}", CONFIG_VERSION),
+                ));
+            }
+        },
+    };
+    if version > CONFIG_VERSION {
+        return Some(ConfigIssue::warning(
+            "version",
+            format!("written by a newer launcher (version {}, this one reads {})", version, CONFIG_VERSION),
+        ));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(config);
+    }
+    config.insert("version".to_string(), json!(CONFIG_VERSION));
+    None
+}
+
+/// Parses `content` one field at a time, so a bad value only resets that field to its
+/// default. `Err` means the file as a whole is unusable.
+pub fn parse(content: &str) -> Result<(Config, Vec<ConfigIssue>), ConfigIssue> {
+    let mut user = match serde_json::from_str::<Value>(content) {
+        Ok(Value::Object(map)) => map,
+        Ok(_) => return Err(ConfigIssue::error("", "config must be a JSON object")),
+        Err(e) => return Err(ConfigIssue::error("", format!("invalid JSON: {}", e))),
+    };
+    let Ok(mut merged) = serde_json::to_value(Config::default()) else {
+        return Ok((Config::default(), Vec::new()));
+    };
+    let mut issues: Vec<ConfigIssue> = migrate(&mut user).into_iter().collect();
+    user.remove("$schema");
+    merge_field(&mut merged, "", "", &schema(), Value::Object(user), &mut issues);
+    let mut config: Config = serde_json::from_value(merged).unwrap_or_default();
+    issues.extend(validate(&mut config));
+    Ok((config, issues))
+}
+
+/// `field.key`, the way [`ConfigIssue::field`] names nested fields.
+fn join_field(field: &str, key: &str) -> String {
+    if field.is_empty() { key.to_string() } else { format!("{}.{}", field, key) }
+}
+
+fn join_pointer(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Schema for `key` inside an object schema; `None` when the object has no such key.
+fn property_schema<'s>(schema: &'s Value, key: &str) -> Option<&'s Value> {
+    schema["properties"].get(key).or_else(|| schema.get("additionalProperties").filter(|s| s.is_object()))
+}
+
+/// Objects without fixed properties, like `profiles`, are maps the user's value replaces.
+fn is_map(schema: &Value) -> bool {
+    schema.get("properties").is_none()
+}
+
+/// Sets, or with `None` removes, the object entry at `pointer`.
+fn put(merged: &mut Value, pointer: &str, value: Option<Value>) {
+    let (parent, key) = pointer.rsplit_once('/').unwrap_or_default();
+    let Some(Value::Object(map)) = merged.pointer_mut(parent) else { return };
+    let key = key.replace("~1", "/").replace("~0", "~");
+    match value {
+        Some(value) => map.insert(key, value),
+        None => map.remove(&key),
+    };
+}
+
+/// `value` without the keys `schema` does not allow, each reported as a warning.
+fn strip_unknown(value: Value, schema: &Value, field: &str, issues: &mut Vec<ConfigIssue>) -> Value {
+    match value {
+        Value::Object(fields) if schema["type"] == "object" => fields.into_iter()
+            .filter_map(|(key, value)| {
+                let field = join_field(field, &key);
+                match property_schema(schema, &key) {
+                    Some(child) => Some((key, strip_unknown(value, child, &field, issues))),
+                    None => {
+                        issues.push(ConfigIssue::warning(&field, "unknown key, ignored"));
+                        None
+                    }
+                }
+            })
+            .collect(),
+        Value::Array(items) if schema["type"] == "array" => items.into_iter()
+            .enumerate()
+            .map(|(i, item)| strip_unknown(item, &schema["items"], &format!("{}[{}]", field, i), issues))
+            .collect(),
+        value => value,
+    }
+}
+
+/// Writes the user's `value` for `field` to `pointer` in `merged`, recursing into the
+/// objects and arrays `schema` describes. Unknown keys are warned about and dropped; a
+/// value that leaves the config unreadable keeps its default, and a bad list item is
+/// skipped on its own.
+fn merge_field(merged: &mut Value, pointer: &str, field: &str, schema: &Value, value: Value, issues: &mut Vec<ConfigIssue>) {
+    let previous = merged.pointer(pointer).cloned();
+    match value {
+        Value::Object(fields) if schema["type"] == "object" => {
+            if is_map(schema) || !previous.as_ref().is_some_and(Value::is_object) {
+                put(merged, pointer, Some(json!({})));
+            }
+            for (key, value) in fields {
+                let field = join_field(field, &key);
+                match property_schema(schema, &key) {
+                    Some(child) => merge_field(merged, &join_pointer(pointer, &key), &field, child, value, issues),
+                    None => issues.push(ConfigIssue::warning(&field, "unknown key, ignored")),
+                }
+            }
+        }
+        Value::Array(items) if schema["type"] == "array" => {
+            put(merged, pointer, Some(json!([])));
+            for (i, item) in items.into_iter().enumerate() {
+                let field = format!("{}[{}]", field, i);
+                let item = strip_unknown(item, &schema["items"], &field, issues);
+                let Some(Value::Array(list)) = merged.pointer_mut(pointer) else { return };
+                list.push(item);
+                if let Err(e) = Config::deserialize(&*merged) {
+                    if let Some(Value::Array(list)) = merged.pointer_mut(pointer) {
+                        list.pop();
+                    }
+                    issues.push(ConfigIssue::error(&field, format!("{}; skipped", e)));
+                }
+            }
+        }
+        value => {
+            put(merged, pointer, Some(value));
+            if let Err(e) = Config::deserialize(&*merged) {
+                issues.push(ConfigIssue::error(field, format!("{}; using the default", e)));
+                put(merged, pointer, previous);
+            }
+        }
+    }
+}
+
+pub type Weight = fn(&mut Config) -> &mut i32;
+
+/// Integer ranking weights and the smallest value [`validate`] accepts for each.
+/// `exact_bonus` may go negative to boost exact matches further.
+pub const WEIGHTS: [(&str, Weight, i32); 5] = [
+    ("frequency_bonus", |c| &mut c.frequency_bonus, 0),
+    ("depth_penalty", |c| &mut c.depth_penalty, 0),
+    ("exact_bonus", |c| &mut c.exact_bonus, i32::MIN),
+    ("prefix_penalty", |c| &mut c.prefix_penalty, 0),
+    ("contains_penalty", |c| &mut c.contains_penalty, 0),
+];
+
+/// Range checks; out-of-range values are reset to their defaults.
+pub fn validate(config: &mut Config) -> Vec<ConfigIssue> {
+    let defaults = Config::default();
+    let mut issues = Vec::new();
+    if !(config.half_life_days.is_finite() && config.half_life_days > 0.0) {
+        issues.push(ConfigIssue::error(
+            "half_life_days",
+            format!("must be greater than 0; using {}", defaults.half_life_days),
+        ));
+        config.half_life_days = defaults.half_life_days;
+    }
+    for (field, weight, minimum) in WEIGHTS {
+        let default = *weight(&mut defaults.clone());
+        let value = weight(config);
+        if *value < minimum {
+            issues.push(ConfigIssue::error(field, format!("must be {} or more; using {}", minimum, default)));
+            *value = default;
+        }
+    }
+
+    let mut names = std::collections::HashSet::new();
+    config.actions.retain(|action| {
+        let field = format!("actions.{}", action.name);
+        if action.name.trim().is_empty() || action.command.trim().is_empty() {
+            issues.push(ConfigIssue::error("actions", "every action needs a name and a command; skipped one"));
+            return false;
+        }
+        if crate::BUILTIN_ACTIONS.iter().any(|(id, _, _)| *id == action.name) {
+            issues.push(ConfigIssue::error(&field, "a built-in action has this name; skipped"));
+            return false;
+        }
+        if !names.insert(action.name.clone()) {
+            issues.push(ConfigIssue::error(&field, "duplicate action name; skipped"));
+            return false;
+        }
+        true
+    });
+
+    config.profiles.retain(|name, profile| {
+        let field = format!("profiles.{}", name);
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            issues.push(ConfigIssue::error(&field, "profile names cannot be empty or contain spaces; skipped"));
+            return false;
+        }
+        for (key, value) in [
+            ("frequency_bonus", &mut profile.frequency_bonus),
+            ("depth_penalty", &mut profile.depth_penalty),
+            ("prefix_penalty", &mut profile.prefix_penalty),
+            ("contains_penalty", &mut profile.contains_penalty),
+        ] {
+            if value.is_some_and(|v| v < 0) {
+                issues.push(ConfigIssue::error(&format!("{}.{}", field, key), "must be 0 or more; not overridden"));
+                *value = None;
+            }
+        }
+        true
+    });
+    if !config.profile.is_empty() && !config.profiles.contains_key(&config.profile) {
+        issues.push(ConfigIssue::error("profile", format!("no profile named {}; using none", config.profile)));
+        config.profile.clear();
+    }
+
+    let home = dirs::home_dir().unwrap_or_default();
+    config.scope.exclude.retain(|pattern| match crate::scope::exclude_globs(pattern, &home) {
+        Ok(_) => true,
+        Err(e) => {
+            issues.push(ConfigIssue::error("scope.exclude", format!("{}; pattern skipped", e)));
+            false
+        }
+    });
+    issues
+}
+
+fn integer(minimum: i32, default: i32, description: &str) -> Value {
+    json!({ "type": "integer", "minimum": minimum, "default": default, "description": description })
+}
+
+/// JSON Schema for `config.json`, printed by `launcher --print-config-schema` and
+/// shipped as `ui/config.schema.json`.
+pub fn schema() -> Value {
+    let defaults = Config::default();
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Launcher config",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "$schema": { "type": "string", "description": "Schema location for editors" },
+            "version": {
+                "type": "integer",
+                "minimum": 0,
+                "default": CONFIG_VERSION,
+                "description": "Config format version; older files are migrated on load"
+            },
+            "half_life_days": {
+                "type": "number",
+                "exclusiveMinimum": 0,
+                "default": defaults.half_life_days,
+                "description": "How long until usage frequency fades by half"
+            },
+            "frequency_bonus": integer(0, defaults.frequency_bonus, "Score bonus for frequently-used items (0 = disabled)"),
+            "prefer_apps": {
+                "type": "boolean",
+                "default": defaults.prefer_apps,
+                "description": "Rank .desktop files higher than regular files"
+            },
+            "penalize_hidden": {
+                "type": "boolean",
+                "default": defaults.penalize_hidden,
+                "description": "Rank items in hidden directories lower"
+            },
+            "depth_penalty": integer(0, defaults.depth_penalty, "Penalty per directory level (0 = no penalty)"),
+            "exact_bonus": {
+                "type": "integer",
+                "default": defaults.exact_bonus,
+                "description": "Score for an exact name match (lower ranks higher; negative values boost exact matches further)"
+            },
+            "prefix_penalty": integer(0, defaults.prefix_penalty, "Score for a name starting with the query"),
+            "contains_penalty": integer(0, defaults.contains_penalty, "Score for a name containing the query"),
+            "transliterate": {
+                "type": "boolean",
+                "default": defaults.transliterate,
+                "description": "Match Cyrillic, CJK and other scripts by their Latin spelling when ranking results, applications and dmenu lists; file search still needs the name as written"
+            },
+            "profile": {
+                "type": "string",
+                "default": defaults.profile,
+                "description": "Profile for queries without an @name prefix (empty = none)"
+            },
+            "profiles": {
+                "type": "object",
+                "default": defaults.profiles,
+                "description": "Named ranking weights, used by starting a query with @name",
+                "additionalProperties": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {
+                        "frequency_bonus": { "type": "integer", "minimum": 0, "description": "Overrides frequency_bonus" },
+                        "prefer_apps": { "type": "boolean", "description": "Overrides prefer_apps" },
+                        "penalize_hidden": { "type": "boolean", "description": "Overrides penalize_hidden" },
+                        "depth_penalty": { "type": "integer", "minimum": 0, "description": "Overrides depth_penalty" },
+                        "exact_bonus": { "type": "integer", "description": "Overrides exact_bonus" },
+                        "prefix_penalty": { "type": "integer", "minimum": 0, "description": "Overrides prefix_penalty" },
+                        "contains_penalty": { "type": "integer", "minimum": 0, "description": "Overrides contains_penalty" }
+                    }
+                }
+            },
+            "terminal": {
+                "type": "object",
+                "additionalProperties": false,
+                "default": { "command": "" },
+                "properties": {
+                    "command": {
+                        "type": "string",
+                        "default": "",
+                        "description": "Command with {dir} and {cmd} placeholders (empty = auto-detect)"
+                    }
+                }
+            },
+            "scope": {
+                "type": "object",
+                "additionalProperties": false,
+                "default": defaults.scope,
+                "description": "Which paths searches may return, for every backend",
+                "properties": {
+                    "include": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "default": defaults.scope.include,
+                        "description": "Only show files under these roots (empty = everywhere); applications are always shown"
+                    },
+                    "exclude": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "default": defaults.scope.exclude,
+                        "description": "Glob patterns to hide; without a leading / or ~ they match at any depth"
+                    },
+                    "boosts": {
+                        "type": "object",
+                        "additionalProperties": { "type": "integer" },
+                        "default": defaults.scope.boosts,
+                        "description": "Score bonus for paths under each root; the deepest matching root counts"
+                    },
+                    "gitignored": {
+                        "enum": ["show", "penalize", "hide"],
+                        "default": defaults.scope.gitignored,
+                        "description": "Paths ignored by .gitignore, .ignore or git's excludes: rank them last or hide them"
+                    }
+                }
+            },
+            "actions": {
+                "type": "array",
+                "default": [],
+                "description": "Custom actions shown in the action list",
+                "items": {
+                    "type": "object",
+                    "required": ["name", "command"],
+                    "additionalProperties": false,
+                    "properties": {
+                        "name": { "type": "string", "minLength": 1, "description": "Label shown in the hint bar" },
+                        "command": {
+                            "type": "string",
+                            "minLength": 1,
+                            "description": "Command with {path}, {dir} and {name} placeholders"
+                        },
+                        "key": { "type": "string", "default": "", "description": "Key binding, e.g. Ctrl+Alt+Enter" },
+                        "extensions": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "default": [],
+                            "description": "Only apply to these file extensions"
+                        },
+                        "mime_types": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "default": [],
+                            "description": "Only apply to these MIME types, e.g. image/*"
+                        },
+                        "terminal": {
+                            "type": "boolean",
+                            "default": false,
+                            "description": "Run inside the configured terminal"
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Adds or replaces `profiles.<name>` in the file at `path`, keeping everything else.
+pub fn save_profile(path: &Path, name: &str, profile: &Profile) -> std::io::Result<()> {
+    let mut user = match fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str::<Value>(&content) {
+            Ok(Value::Object(map)) => map,
+            _ => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "not a JSON object")),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Map::new(),
+        Err(e) => return Err(e),
+    };
+    // Writing `profiles` replaces the defaults, so they are copied in with the new one.
+    let profiles = user.entry("profiles").or_insert_with(|| json!(Config::default().profiles));
+    let Some(profiles) = profiles.as_object_mut() else {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "profiles is not an object"));
+    };
+    profiles.insert(name.to_string(), json!(profile));
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&user)? + "\n")
+}
+
+struct Loaded {
+    config: Arc<Config>,
+    issues: Vec<ConfigIssue>,
+}
+
+/// Reads `path`, keeping `previous` when the file exists but cannot be used at all.
+fn load(path: &Path, previous: Option<Arc<Config>>) -> Loaded {
+    let fallback = || previous.clone().unwrap_or_default();
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Loaded { config: Arc::default(), issues: Vec::new() };
+        }
+        Err(e) => {
+            let issue = ConfigIssue::error("", format!("cannot read {}: {}", path.display(), e));
+            return Loaded { config: fallback(), issues: vec![issue] };
+        }
+    };
+    let loaded = match parse(&content) {
+        Ok((config, issues)) => Loaded { config: Arc::new(config), issues },
+        Err(issue) => Loaded { config: fallback(), issues: vec![issue] },
+    };
+    for issue in &loaded.issues {
+        eprintln!("launcher: {}: {}", path.display(), issue);
+    }
+    loaded
+}
+
+fn store() -> &'static RwLock<Loaded> {
+    static STORE: OnceLock<RwLock<Loaded>> = OnceLock::new();
+    STORE.get_or_init(|| RwLock::new(load(&config_path(), None)))
+}
+
+/// The config as of the last (re)load.
+pub fn current() -> Arc<Config> {
+    store().read().unwrap().config.clone()
+}
+
+pub fn issues() -> Vec<ConfigIssue> {
+    store().read().unwrap().issues.clone()
+}
+
+/// Re-reads the file now and returns the new issues.
+pub fn reload() -> Vec<ConfigIssue> {
+    let previous = current();
+    let loaded = load(&config_path(), Some(previous));
+    let issues = loaded.issues.clone();
+    *store().write().unwrap() = loaded;
+    issues
+}
+
+fn fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Polls the config file and calls `on_change` after each reload.
+pub fn watch(on_change: impl Fn() + Send + 'static) {
+    let path = config_path();
+    let mut last = fingerprint(&path);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        let now = fingerprint(&path);
+        if now != last {
+            last = now;
+            reload();
+            on_change();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn empty_object_gives_defaults() {
+            let (config, issues) = parse("{}").unwrap();
+            assert_eq!(config, Config::default());
+            assert!(issues.is_empty());
+        }
+
+        #[test]
+        fn reads_valid_fields() {
+            // Act
+            let (config, issues) = parse(r#"{"half_life_days": 14, "prefer_apps": false}"#).unwrap();
+
+            // Assert
+            assert_eq!(config.half_life_days, 14.0);
+            assert!(!config.prefer_apps);
+            assert!(issues.is_empty());
+        }
+
+        #[test]
+        fn bad_field_only_resets_that_field() {
+            // Act
+            let (config, issues) = parse(r#"{"frequency_bonus": "lots", "depth_penalty": 5}"#).unwrap();
+
+            // Assert
+            assert_eq!(config.frequency_bonus, 500);
+            assert_eq!(config.depth_penalty, 5);
+            assert_eq!(issues.len(), 1);
+            assert_eq!(issues[0].field, "frequency_bonus");
+            assert_eq!(issues[0].severity, Severity::Error);
+        }
+
+        #[test]
+        fn unknown_keys_are_warnings() {
+            // Act
+            let (config, issues) = parse(r#"{"half_lif_days": 3}"#).unwrap();
+
+            // Assert
+            assert_eq!(config, Config::default());
+            assert_eq!(issues, vec![ConfigIssue::warning("half_lif_days", "unknown key, ignored")]);
+        }
+
+        #[test]
+        fn invalid_json_is_an_error() {
+            let issue = parse(r#"{"half_life_days": 3,}"#).unwrap_err();
+            assert_eq!(issue.field, "");
+            assert!(issue.message.starts_with("invalid JSON"));
+        }
+
+        #[test]
+        fn missing_fields_use_defaults() {
+            let (config, _) = parse(r#"{"terminal": {}, "actions": [{"name": "a", "command": "b"}]}"#).unwrap();
+            assert_eq!(config.terminal, TerminalConfig::default());
+            assert_eq!(config.actions[0].key, "");
+        }
+
+        #[test]
+        fn partial_scope_keeps_other_defaults() {
+            let (config, _) = parse(r#"{"scope": {"include": ["~/code"]}}"#).unwrap();
+            assert_eq!(config.scope.include, vec!["~/code"]);
+            assert_eq!(config.scope.exclude, ScopeConfig::default().exclude);
+        }
+
+        #[test]
+        fn accepts_schema_key() {
+            let (_, issues) = parse(r#"{"$schema": "config.schema.json"}"#).unwrap();
+            assert!(issues.is_empty());
+        }
+
+        #[test]
+        fn non_object_is_an_error() {
+            assert!(parse("[1, 2]").is_err());
+        }
+
+        #[test]
+        fn bad_action_entry_reports_its_index() {
+            let (config, issues) = parse(r#"{"actions": [{"name": "x"}]}"#).unwrap();
+            assert!(config.actions.is_empty());
+            assert_eq!(issues[0].field, "actions[0]");
+        }
+
+        #[test]
+        fn bad_action_keeps_the_others() {
+            // Act
+            let (config, issues) = parse(r#"{"actions": [
+                {"name": "a", "command": "x"},
+                {"name": "b", "command": "y", "terminal": "yes"},
+                {"name": "c", "command": "z"}
+            ]}"#).unwrap();
+
+            // Assert
+            let names: Vec<_> = config.actions.iter().map(|a| a.name.as_str()).collect();
+            assert_eq!(names, ["a", "c"]);
+            assert_eq!(issues.len(), 1);
+            assert_eq!(issues[0].field, "actions[1]");
+        }
+
+        #[test]
+        fn unknown_nested_keys_are_warnings() {
+            // Act
+            let (config, issues) = parse(r#"{
+                "scope": {"exclud": ["x"]},
+                "profiles": {"work": {"depth_penalt": 1}},
+                "actions": [{"name": "a", "command": "x", "extentions": ["pdf"]}]
+            }"#).unwrap();
+
+            // Assert
+            let fields: Vec<_> = issues.iter().map(|i| (i.field.as_str(), &i.severity)).collect();
+            assert_eq!(fields, [
+                ("actions[0].extentions", &Severity::Warning),
+                ("profiles.work.depth_penalt", &Severity::Warning),
+                ("scope.exclud", &Severity::Warning),
+            ]);
+            assert_eq!(config.actions[0].name, "a");
+            assert_eq!(config.profiles["work"], Profile::default());
+            assert_eq!(config.scope, ScopeConfig::default());
+        }
+
+        #[test]
+        fn bad_scope_field_keeps_the_valid_ones() {
+            // Act
+            let (config, issues) = parse(r#"{"scope": {"include": ["~/code"], "gitignored": "sometimes"}}"#).unwrap();
+
+            // Assert
+            assert_eq!(config.scope.include, vec!["~/code"]);
+            assert_eq!(config.scope.gitignored, GitIgnored::default());
+            assert_eq!(issues.len(), 1);
+            assert_eq!(issues[0].field, "scope.gitignored");
+        }
+
+        #[test]
+        fn bad_profile_weight_keeps_the_profile() {
+            // Act
+            let (config, issues) = parse(r#"{"profiles": {"work": {"depth_penalty": "deep", "prefer_apps": false}}}"#).unwrap();
+
+            // Assert
+            assert_eq!(config.profiles.keys().collect::<Vec<_>>(), ["work"]);
+            assert_eq!(config.profiles["work"].prefer_apps, Some(false));
+            assert_eq!(config.profiles["work"].depth_penalty, None);
+            assert_eq!(issues[0].field, "profiles.work.depth_penalty");
+        }
+
+        #[test]
+        fn boosts_with_slashes_are_read() {
+            // Act
+            let (config, issues) = parse(r#"{"scope": {"boosts": {"~/code/work": 80, "/srv": "high"}}}"#).unwrap();
+
+            // Assert
+            assert_eq!(config.scope.boosts, BTreeMap::from([("~/code/work".to_string(), 80)]));
+            assert_eq!(issues[0].field, "scope.boosts./srv");
+        }
+    }
+
+    mod migrate {
+        use super::*;
+
+        fn object(content: &str) -> Map<String, Value> {
+            serde_json::from_str(content).unwrap()
+        }
+
+        #[test]
+        fn chain_covers_every_version() {
+            assert_eq!(MIGRATIONS.len() as u64, CONFIG_VERSION);
+        }
+
+        #[test]
+        fn stamps_unversioned_files() {
+            // Arrange
+            let mut config = object(r#"{"depth_penalty": 4}"#);
+
+            // Act
+            let issue = migrate(&mut config);
+
+            // Assert
+            assert_eq!(issue, None);
+            assert_eq!(config["version"], json!(CONFIG_VERSION));
+            assert_eq!(config["depth_penalty"], json!(4));
+        }
+
+        #[test]
+        fn warns_about_newer_versions() {
+            // Arrange
+            let mut config = object(r#"{"version": 99}"#);
+
+            // Act
+            let issue = migrate(&mut config).unwrap();
+
+            // Assert
+            assert_eq!(issue.severity, Severity::Warning);
+            assert_eq!(config["version"], json!(99));
+        }
+
+        #[test]
+        fn rejects_non_numeric_version() {
+            // Arrange
+            let mut config = object(r#"{"version": "1"}"#);
+
+            // Act
+            let issue = migrate(&mut config).unwrap();
+
+            // Assert
+            assert_eq!(issue.severity, Severity::Error);
+            assert_eq!(config["version"], json!(CONFIG_VERSION));
+        }
+    }
+
+    mod schema {
+        use super::*;
+
+        fn properties() -> Map<String, Value> {
+            schema()["properties"].as_object().unwrap().clone()
+        }
+
+        #[test]
+        fn describes_every_field() {
+            // Arrange
+            let Value::Object(fields) = serde_json::to_value(Config::default()).unwrap() else { panic!() };
+            let mut documented: Vec<String> = properties().keys().filter(|k| *k != "$schema").cloned().collect();
+            let mut expected: Vec<String> = fields.keys().cloned().collect();
+
+            // Act
+            documented.sort();
+            expected.sort();
+
+            // Assert
+            assert_eq!(documented, expected);
+        }
+
+        #[test]
+        fn defaults_match_config_default() {
+            let Value::Object(fields) = serde_json::to_value(Config::default()).unwrap() else { panic!() };
+            let properties = properties();
+            for (key, value) in fields {
+                assert_eq!(properties[&key]["default"], value, "default of {}", key);
+            }
+        }
+
+        #[test]
+        fn ui_copy_is_up_to_date() {
+            let shipped: Value = serde_json::from_str(include_str!("../ui/config.schema.json")).unwrap();
+            assert_eq!(shipped, schema(), "run `launcher --print-config-schema > ui/config.schema.json`");
+        }
+    }
+
+    mod validate {
+        use super::*;
+
+        #[test]
+        fn defaults_are_valid() {
+            assert!(validate(&mut Config::default()).is_empty());
+        }
+
+        #[test]
+        fn half_life_must_be_positive() {
+            // Arrange
+            let mut config = Config { half_life_days: 0.0, ..Config::default() };
+
+            // Act
+            let issues = validate(&mut config);
+
+            // Assert
+            assert_eq!(config.half_life_days, 7.0);
+            assert_eq!(issues[0].to_string(), "half_life_days: must be greater than 0; using 7");
+        }
+
+        #[test]
+        fn negative_penalties_are_reset() {
+            // Arrange
+            let mut config = Config { depth_penalty: -1, prefix_penalty: -5, ..Config::default() };
+
+            // Act
+            let issues = validate(&mut config);
+
+            // Assert
+            assert_eq!(config.depth_penalty, 2);
+            assert_eq!(config.prefix_penalty, 100);
+            assert_eq!(issues.len(), 2);
+        }
+
+        #[test]
+        fn negative_exact_bonus_is_kept() {
+            // Arrange
+            let mut config = Config { exact_bonus: -50, ..Config::default() };
+            config.profiles.insert("mine".to_string(), Profile { exact_bonus: Some(-80), ..Profile::default() });
+
+            // Act
+            let issues = validate(&mut config);
+
+            // Assert
+            assert_eq!(config.exact_bonus, -50);
+            assert_eq!(config.profiles["mine"].exact_bonus, Some(-80));
+            assert!(issues.is_empty());
+        }
+
+        #[test]
+        fn drops_incomplete_and_duplicate_actions() {
+            // Arrange
+            let action = |name: &str, command: &str| CustomAction {
+                name: name.to_string(),
+                command: command.to_string(),
+                key: String::new(),
+                extensions: Vec::new(),
+                mime_types: Vec::new(),
+                terminal: false,
+            };
+            let mut config = Config {
+                actions: vec![action("a", "x"), action("", "y"), action("a", "z"), action("copy", "w")],
+                ..Config::default()
+            };
+
+            // Act
+            let issues = validate(&mut config);
+
+            // Assert
+            assert_eq!(config.actions, vec![action("a", "x")]);
+            assert_eq!(issues.len(), 3);
+            assert_eq!(issues[2].field, "actions.copy");
+        }
+
+        #[test]
+        fn drops_invalid_exclude_globs() {
+            // Arrange
+            let mut config = Config::default();
+            config.scope.exclude = vec!["dist".to_string(), "[oops".to_string()];
+
+            // Act
+            let issues = validate(&mut config);
+
+            // Assert
+            assert_eq!(config.scope.exclude, vec!["dist"]);
+            assert_eq!(issues[0].field, "scope.exclude");
+        }
+
+        #[test]
+        fn drops_bad_profile_names_and_weights() {
+            // Arrange
+            let mut config = Config::default();
+            config.profiles.insert("two words".to_string(), Profile::default());
+            config.profiles.insert("mine".to_string(), Profile { depth_penalty: Some(-1), ..Profile::default() });
+
+            // Act
+            let issues = validate(&mut config);
+
+            // Assert
+            assert!(!config.profiles.contains_key("two words"));
+            assert_eq!(config.profiles["mine"], Profile::default());
+            assert_eq!(issues.len(), 2);
+        }
+
+        #[test]
+        fn unknown_default_profile_is_cleared() {
+            let mut config = Config { profile: "missing".to_string(), ..Config::default() };
+            assert_eq!(validate(&mut config)[0].field, "profile");
+            assert!(config.profile.is_empty());
+        }
+    }
+
+    mod for_query {
+        use super::*;
+
+        #[test]
+        fn prefix_selects_profile_and_is_stripped() {
+            // Act
+            let (config, query) = Config::default().for_query("@code main.rs");
+
+            // Assert
+            assert_eq!(query, "main.rs");
+            assert!(!config.prefer_apps);
+            assert_eq!(config.depth_penalty, 0);
+            assert_eq!(config.prefix_penalty, Config::default().prefix_penalty);
+        }
+
+        #[test]
+        fn unknown_prefix_stays_in_query() {
+            let (config, query) = Config::default().for_query("@someone notes");
+            assert_eq!(query, "@someone notes");
+            assert_eq!(config, Config::default());
+        }
+
+        #[test]
+        fn default_profile_applies_without_prefix() {
+            let base = Config { profile: "documents".to_string(), ..Config::default() };
+            let (config, query) = base.for_query("invoice");
+            assert_eq!(query, "invoice");
+            assert_eq!(config.contains_penalty, 150);
+        }
+    }
+
+    mod save_profile {
+        use super::*;
+        use tempfile::tempdir;
+
+        #[test]
+        fn adds_profile_and_keeps_other_keys() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("config.json");
+            fs::write(&path, r#"{"depth_penalty": 5}"#).unwrap();
+            let profile = Profile { exact_bonus: Some(3), ..Profile::default() };
+
+            // Act
+            save_profile(&path, "tuned", &profile).unwrap();
+            let (config, issues) = parse(&fs::read_to_string(&path).unwrap()).unwrap();
+
+            // Assert
+            assert!(issues.is_empty());
+            assert_eq!(config.depth_penalty, 5);
+            assert_eq!(config.profiles["tuned"], profile);
+            assert!(config.profiles.contains_key("code"));
+        }
+
+        #[test]
+        fn refuses_to_overwrite_broken_file() {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("config.json");
+            fs::write(&path, "{ not json").unwrap();
+            assert!(save_profile(&path, "tuned", &Profile::default()).is_err());
+            assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+        }
+    }
+
+    mod load {
+        use super::*;
+        use tempfile::tempdir;
+
+        #[test]
+        fn missing_file_gives_defaults_without_issues() {
+            let dir = tempdir().unwrap();
+            let loaded = load(&dir.path().join("config.json"), None);
+            assert_eq!(*loaded.config, Config::default());
+            assert!(loaded.issues.is_empty());
+        }
+
+        #[test]
+        fn broken_file_keeps_previous_config() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("config.json");
+            fs::write(&path, "{ not json").unwrap();
+            let previous = Arc::new(Config { depth_penalty: 9, ..Config::default() });
+
+            // Act
+            let loaded = load(&path, Some(previous));
+
+            // Assert
+            assert_eq!(loaded.config.depth_penalty, 9);
+            assert_eq!(loaded.issues.len(), 1);
+        }
+    }
+}
@@ -0,0 +1,699 @@
+//! Desktop entries and MIME associations, following the freedesktop.org Desktop Entry
+//! and MIME Applications Associations specifications.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DesktopEntry {
+    pub id: String,
+    pub path: PathBuf,
+    pub name: String,
+    pub exec: String,
+    pub icon: Option<String>,
+    pub mime_types: Vec<String>,
+    pub terminal: bool,
+    pub hidden: bool,
+    /// `NoDisplay=true`: usable for opening files but kept out of application lists.
+    pub no_display: bool,
+    pub keywords: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MimeAssociations {
+    pub default: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+fn xdg_data_dirs() -> Vec<PathBuf> {
+    let dirs = env::var("XDG_DATA_DIRS").ok().filter(|d| !d.is_empty());
+    let dirs = dirs.as_deref().unwrap_or("/usr/local/share:/usr/share");
+    dirs::data_dir().into_iter()
+        .chain(dirs.split(':').filter(|d| !d.is_empty()).map(PathBuf::from))
+        .collect()
+}
+
+fn xdg_config_dirs() -> Vec<PathBuf> {
+    let dirs = env::var("XDG_CONFIG_DIRS").ok().filter(|d| !d.is_empty());
+    let dirs = dirs.as_deref().unwrap_or("/etc/xdg");
+    dirs::config_dir().into_iter()
+        .chain(dirs.split(':').filter(|d| !d.is_empty()).map(PathBuf::from))
+        .collect()
+}
+
+/// Application directories in precedence order, highest first. Flatpak and snap
+/// exports follow at the lowest precedence for sessions that leave them out of
+/// `XDG_DATA_DIRS`.
+pub fn application_dirs() -> Vec<PathBuf> {
+    let exports = dirs::data_dir().map(|d| d.join("flatpak/exports/share")).into_iter()
+        .chain(["/var/lib/flatpak/exports/share", "/var/lib/snapd/desktop"].map(PathBuf::from));
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in xdg_data_dirs().into_iter().chain(exports).map(|d| d.join("applications")) {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// `mimeapps.list` files in precedence order, highest first.
+pub fn mimeapps_paths() -> Vec<PathBuf> {
+    let desktops: Vec<String> = env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|d| !d.is_empty())
+        .map(|d| d.to_lowercase())
+        .collect();
+    let config = xdg_config_dirs();
+    let data = xdg_data_dirs().into_iter().map(|d| d.join("applications"));
+    config.into_iter().chain(data)
+        .flat_map(|dir| {
+            desktops.iter()
+                .map(|d| dir.join(format!("{}-mimeapps.list", d)))
+                .chain(std::iter::once(dir.join("mimeapps.list")))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn unescape_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value.split(';').map(str::trim).filter(|v| !v.is_empty()).map(str::to_string).collect()
+}
+
+/// Parses the `[Desktop Entry]` group. Returns `None` for anything but applications.
+pub fn parse_desktop_entry(id: &str, path: &Path, content: &str) -> Option<DesktopEntry> {
+    let mut entry = DesktopEntry { id: id.to_string(), path: path.to_path_buf(), ..Default::default() };
+    let mut in_main = false;
+    let mut is_app = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let value = value.trim();
+        match key.trim() {
+            "Type" => is_app = value == "Application",
+            "Name" => entry.name = unescape_value(value),
+            "Exec" => entry.exec = unescape_value(value),
+            "Icon" => entry.icon = Some(unescape_value(value)).filter(|i| !i.is_empty()),
+            "MimeType" => entry.mime_types = split_list(value),
+            "Terminal" => entry.terminal = value == "true",
+            "Hidden" => entry.hidden = value == "true",
+            "NoDisplay" => entry.no_display = value == "true",
+            "Keywords" => entry.keywords = split_list(&unescape_value(value)),
+            _ => {}
+        }
+    }
+    is_app.then_some(entry)
+}
+
+/// The desktop file ID: the path below the applications dir with `/` replaced by `-`.
+pub fn desktop_id(apps_dir: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(apps_dir).unwrap_or(file);
+    relative.to_string_lossy().replace('/', "-")
+}
+
+fn collect_desktop_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(read) = fs::read_dir(dir) else { return };
+    let mut paths: Vec<PathBuf> = read.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            collect_desktop_files(&path, out);
+        } else if path.extension().is_some_and(|e| e == "desktop") {
+            out.push(path);
+        }
+    }
+}
+
+/// Loads every application entry, keeping the first occurrence of each desktop ID.
+/// A `Hidden=true` entry masks lower-precedence entries with the same ID.
+pub fn load_desktop_entries(dirs: &[PathBuf]) -> Vec<DesktopEntry> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for dir in dirs {
+        let mut files = Vec::new();
+        collect_desktop_files(dir, &mut files);
+        for file in files {
+            let id = desktop_id(dir, &file);
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&file) else { continue };
+            if let Some(entry) = parse_desktop_entry(&id, &file, &content) {
+                if !entry.hidden {
+                    entries.push(entry);
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Extracts the associations for `mime` from one `mimeapps.list` file.
+pub fn parse_mimeapps(content: &str, mime: &str) -> MimeAssociations {
+    let mut assoc = MimeAssociations::default();
+    let mut section = "";
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line;
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        if key.trim() != mime {
+            continue;
+        }
+        let ids = split_list(value);
+        match section {
+            "[Default Applications]" => assoc.default.extend(ids),
+            "[Added Associations]" => assoc.added.extend(ids),
+            "[Removed Associations]" => assoc.removed.extend(ids),
+            _ => {}
+        }
+    }
+    assoc
+}
+
+pub fn load_mimeapps(mime: &str) -> Vec<MimeAssociations> {
+    mimeapps_paths().iter()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .map(|content| parse_mimeapps(&content, mime))
+        .collect()
+}
+
+/// The first installed default application, in precedence order.
+pub fn default_app(lists: &[MimeAssociations], entries: &[DesktopEntry]) -> Option<String> {
+    lists.iter()
+        .flat_map(|l| &l.default)
+        .find(|id| entries.iter().any(|e| &e.id == *id))
+        .cloned()
+}
+
+/// Desktop IDs able to open `mime`, default first, then added associations,
+/// then entries declaring the type in `MimeType=`. Removals apply to their own
+/// file and everything of lower precedence.
+pub fn associated_apps(lists: &[MimeAssociations], entries: &[DesktopEntry], mime: &str) -> Vec<String> {
+    let installed: HashSet<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+    let mut removed: HashSet<&str> = HashSet::new();
+    let mut apps: Vec<String> = Vec::new();
+    for list in lists {
+        removed.extend(list.removed.iter().map(String::as_str));
+        for id in &list.added {
+            if installed.contains(id.as_str()) && !removed.contains(id.as_str()) && !apps.contains(id) {
+                apps.push(id.clone());
+            }
+        }
+    }
+    for entry in entries {
+        let declares = entry.mime_types.iter().any(|m| m == mime);
+        if declares && !removed.contains(entry.id.as_str()) && !apps.contains(&entry.id) {
+            apps.push(entry.id.clone());
+        }
+    }
+    if let Some(default) = default_app(lists, entries) {
+        apps.retain(|id| *id != default);
+        apps.insert(0, default);
+    }
+    apps
+}
+
+/// Splits an `Exec` value into arguments, honouring the spec's double-quote rules.
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            '\\' if quoted => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// File name of the program `Exec` runs, e.g. `firefox` for `/usr/bin/firefox %u`.
+pub fn exec_program(entry: &DesktopEntry) -> Option<String> {
+    let program = split_exec(&entry.exec).into_iter().next()?;
+    Path::new(&program).file_name().map(|n| n.to_string_lossy().to_string())
+}
+
+fn as_uri(file: &str) -> String {
+    if Path::new(file).is_absolute() {
+        crate::file_uri(file)
+    } else {
+        file.to_string()
+    }
+}
+
+/// Expands `Exec` field codes. Files are inserted as whole arguments and are never re-parsed.
+pub fn expand_exec(entry: &DesktopEntry, files: &[String]) -> Vec<String> {
+    let mut argv = Vec::new();
+    for arg in split_exec(&entry.exec) {
+        match arg.as_str() {
+            "%f" => argv.extend(files.first().cloned()),
+            "%F" => argv.extend(files.iter().cloned()),
+            "%u" => argv.extend(files.first().map(|f| as_uri(f))),
+            "%U" => argv.extend(files.iter().map(|f| as_uri(f))),
+            "%i" => {
+                if let Some(icon) = &entry.icon {
+                    argv.push("--icon".to_string());
+                    argv.push(icon.clone());
+                }
+            }
+            "%c" => argv.push(entry.name.clone()),
+            "%k" => argv.push(entry.path.to_string_lossy().to_string()),
+            "%d" | "%D" | "%n" | "%N" | "%v" | "%m" => {}
+            _ => argv.push(expand_inline_codes(&arg, entry, files)),
+        }
+    }
+    argv
+}
+
+fn expand_inline_codes(arg: &str, entry: &DesktopEntry, files: &[String]) -> String {
+    let mut out = String::new();
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => out.push('%'),
+            Some('f') | Some('F') => out.push_str(files.first().map(String::as_str).unwrap_or("")),
+            Some('u') | Some('U') => out.push_str(&files.first().map(|f| as_uri(f)).unwrap_or_default()),
+            Some('c') => out.push_str(&entry.name),
+            Some('k') => out.push_str(&entry.path.to_string_lossy()),
+            _ => {}
+        }
+    }
+    out
+}
+
+pub fn launch(entry: &DesktopEntry, files: &[String], terminal: &crate::TerminalConfig) -> std::io::Result<Child> {
+    let argv = expand_exec(entry, files);
+    let Some((program, args)) = argv.split_first() else {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{} has no Exec line", entry.id)));
+    };
+    let dir = files.first()
+        .map(|f| crate::get_dir(f))
+        .or_else(|| dirs::home_dir().map(|h| h.to_string_lossy().to_string()))
+        .unwrap_or_else(|| ".".to_string());
+    #[cfg(target_os = "linux")]
+    if entry.terminal {
+        return crate::spawn_terminal(&dir, &argv, terminal);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = terminal;
+    Command::new(program).args(args).current_dir(dir).spawn()
+}
+
+pub fn set_default_app(id: &str, mime: &str) -> std::io::Result<()> {
+    let status = Command::new("xdg-mime")
+        .args(["default", id, mime])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("xdg-mime exited with {}", status)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, mime_types: &[&str]) -> DesktopEntry {
+        DesktopEntry {
+            id: id.to_string(),
+            path: PathBuf::from(format!("/usr/share/applications/{}", id)),
+            name: id.trim_end_matches(".desktop").to_string(),
+            exec: "app %F".to_string(),
+            mime_types: mime_types.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn ids(list: &[&str]) -> Vec<String> {
+        list.iter().map(|i| i.to_string()).collect()
+    }
+
+    mod parse_desktop_entry {
+        use super::*;
+
+        #[test]
+        fn reads_main_group_only() {
+            // Arrange
+            let content = "[Desktop Entry]\nType=Application\nName=Editor\nExec=edit %f\nIcon=edit\n\
+                MimeType=text/plain;text/markdown;\n\n[Desktop Action new]\nName=New Window\nExec=edit --new\n";
+
+            // Act
+            let e = parse_desktop_entry("edit.desktop", Path::new("/x/edit.desktop"), content).unwrap();
+
+            // Assert
+            assert_eq!(e.name, "Editor");
+            assert_eq!(e.exec, "edit %f");
+            assert_eq!(e.icon.as_deref(), Some("edit"));
+            assert_eq!(e.mime_types, ["text/plain", "text/markdown"]);
+        }
+
+        #[test]
+        fn rejects_non_applications() {
+            // Arrange
+            let content = "[Desktop Entry]\nType=Link\nName=Site\nURL=https://example.com\n";
+
+            // Act
+            let e = parse_desktop_entry("site.desktop", Path::new("/x"), content);
+
+            // Assert
+            assert!(e.is_none());
+        }
+
+        #[test]
+        fn unescapes_values() {
+            // Arrange
+            let content = "[Desktop Entry]\nType=Application\nName=A\\sB\nExec=a\n";
+
+            // Act
+            let e = parse_desktop_entry("a.desktop", Path::new("/x"), content).unwrap();
+
+            // Assert
+            assert_eq!(e.name, "A B");
+        }
+
+        #[test]
+        fn reads_flags() {
+            // Arrange
+            let content = "[Desktop Entry]\nType=Application\nName=A\nExec=a\nTerminal=true\nHidden=true\nNoDisplay=true\n";
+
+            // Act
+            let e = parse_desktop_entry("a.desktop", Path::new("/x"), content).unwrap();
+
+            // Assert
+            assert!(e.terminal);
+            assert!(e.hidden);
+            assert!(e.no_display);
+        }
+
+        #[test]
+        fn reads_unlocalized_keywords() {
+            // Arrange
+            let content = "[Desktop Entry]\nType=Application\nName=A\nExec=a\nKeywords=web;browser;\nKeywords[de]=Netz;\n";
+
+            // Act
+            let e = parse_desktop_entry("a.desktop", Path::new("/x"), content).unwrap();
+
+            // Assert
+            assert_eq!(e.keywords, ["web", "browser"]);
+        }
+    }
+
+    mod desktop_id {
+        use super::*;
+
+        #[test]
+        fn uses_file_name_at_top_level() {
+            // Act
+            let id = desktop_id(Path::new("/usr/share/applications"), Path::new("/usr/share/applications/foo.desktop"));
+
+            // Assert
+            assert_eq!(id, "foo.desktop");
+        }
+
+        #[test]
+        fn joins_subdirectories_with_dashes() {
+            // Act
+            let id = desktop_id(Path::new("/usr/share/applications"), Path::new("/usr/share/applications/kde4/foo.desktop"));
+
+            // Assert
+            assert_eq!(id, "kde4-foo.desktop");
+        }
+    }
+
+    mod load_desktop_entries {
+        use super::*;
+        use tempfile::tempdir;
+
+        fn write(dir: &Path, name: &str, content: &str) {
+            fs::create_dir_all(dir).unwrap();
+            fs::write(dir.join(name), content).unwrap();
+        }
+
+        #[test]
+        fn first_directory_wins_for_same_id() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let (high, low) = (root.path().join("high"), root.path().join("low"));
+            write(&high, "a.desktop", "[Desktop Entry]\nType=Application\nName=High\nExec=a\n");
+            write(&low, "a.desktop", "[Desktop Entry]\nType=Application\nName=Low\nExec=a\n");
+
+            // Act
+            let entries = load_desktop_entries(&[high, low]);
+
+            // Assert
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].name, "High");
+        }
+
+        #[test]
+        fn hidden_entry_masks_lower_precedence() {
+            // Arrange
+            let root = tempdir().unwrap();
+            let (high, low) = (root.path().join("high"), root.path().join("low"));
+            write(&high, "a.desktop", "[Desktop Entry]\nType=Application\nName=A\nExec=a\nHidden=true\n");
+            write(&low, "a.desktop", "[Desktop Entry]\nType=Application\nName=A\nExec=a\n");
+
+            // Act
+            let entries = load_desktop_entries(&[high, low]);
+
+            // Assert
+            assert!(entries.is_empty());
+        }
+    }
+
+    mod associations {
+        use super::*;
+
+        const LIST: &str = "[Default Applications]\ntext/plain=gedit.desktop;vim.desktop\n\n\
+            [Added Associations]\ntext/plain=code.desktop;\nimage/png=gimp.desktop\n\n\
+            [Removed Associations]\ntext/plain=nano.desktop\n";
+
+        #[test]
+        fn parses_sections_for_requested_type() {
+            // Act
+            let assoc = parse_mimeapps(LIST, "text/plain");
+
+            // Assert
+            assert_eq!(assoc.default, ids(&["gedit.desktop", "vim.desktop"]));
+            assert_eq!(assoc.added, ids(&["code.desktop"]));
+            assert_eq!(assoc.removed, ids(&["nano.desktop"]));
+        }
+
+        #[test]
+        fn default_skips_uninstalled_apps() {
+            // Arrange
+            let lists = [parse_mimeapps(LIST, "text/plain")];
+            let entries = [entry("vim.desktop", &[])];
+
+            // Act
+            let default = default_app(&lists, &entries);
+
+            // Assert
+            assert_eq!(default.as_deref(), Some("vim.desktop"));
+        }
+
+        #[test]
+        fn orders_default_then_added_then_declared() {
+            // Arrange
+            let lists = [parse_mimeapps(LIST, "text/plain")];
+            let entries = [
+                entry("kate.desktop", &["text/plain"]),
+                entry("code.desktop", &[]),
+                entry("gedit.desktop", &["text/plain"]),
+            ];
+
+            // Act
+            let apps = associated_apps(&lists, &entries, "text/plain");
+
+            // Assert
+            assert_eq!(apps, ids(&["gedit.desktop", "code.desktop", "kate.desktop"]));
+        }
+
+        #[test]
+        fn removed_association_hides_declared_mime_type() {
+            // Arrange
+            let lists = [parse_mimeapps(LIST, "text/plain")];
+            let entries = [entry("nano.desktop", &["text/plain"])];
+
+            // Act
+            let apps = associated_apps(&lists, &entries, "text/plain");
+
+            // Assert
+            assert!(apps.is_empty());
+        }
+
+        #[test]
+        fn removal_does_not_affect_higher_precedence_files() {
+            // Arrange
+            let high = MimeAssociations { added: ids(&["nano.desktop"]), ..Default::default() };
+            let low = MimeAssociations { removed: ids(&["nano.desktop"]), ..Default::default() };
+            let entries = [entry("nano.desktop", &[])];
+
+            // Act
+            let apps = associated_apps(&[high, low], &entries, "text/plain");
+
+            // Assert
+            assert_eq!(apps, ids(&["nano.desktop"]));
+        }
+    }
+
+    mod expand_exec {
+        use super::*;
+
+        fn with_exec(exec: &str) -> DesktopEntry {
+            DesktopEntry { exec: exec.to_string(), icon: Some("ed".to_string()), name: "Ed".to_string(), ..entry("ed.desktop", &[]) }
+        }
+
+        #[test]
+        fn expands_single_file() {
+            // Act
+            let argv = expand_exec(&with_exec("ed %f"), &ids(&["/a b/c.txt"]));
+
+            // Assert
+            assert_eq!(argv, ["ed", "/a b/c.txt"]);
+        }
+
+        #[test]
+        fn expands_file_list() {
+            // Act
+            let argv = expand_exec(&with_exec("ed %F"), &ids(&["/a", "/b"]));
+
+            // Assert
+            assert_eq!(argv, ["ed", "/a", "/b"]);
+        }
+
+        #[test]
+        fn expands_urls() {
+            // Act
+            let argv = expand_exec(&with_exec("ed %u"), &ids(&["/a b"]));
+
+            // Assert
+            assert_eq!(argv, ["ed", "file:///a%20b"]);
+        }
+
+        #[test]
+        fn expands_icon_and_name() {
+            // Act
+            let argv = expand_exec(&with_exec("ed %i --title=%c"), &[]);
+
+            // Assert
+            assert_eq!(argv, ["ed", "--icon", "ed", "--title=Ed"]);
+        }
+
+        #[test]
+        fn drops_missing_file_and_deprecated_codes() {
+            // Act
+            let argv = expand_exec(&with_exec("ed %f %d %m"), &[]);
+
+            // Assert
+            assert_eq!(argv, ["ed"]);
+        }
+
+        #[test]
+        fn honours_quoted_arguments_and_escapes() {
+            // Act
+            let argv = expand_exec(&with_exec(r#"sh -c "echo \"hi\" 100%%" %f"#), &ids(&["/x"]));
+
+            // Assert
+            assert_eq!(argv, ["sh", "-c", r#"echo "hi" 100%"#, "/x"]);
+        }
+
+        #[test]
+        fn hostile_filename_stays_one_argument() {
+            // Act
+            let argv = expand_exec(&with_exec("ed %f"), &ids(&["/tmp/$(rm -rf ~)'\""]));
+
+            // Assert
+            assert_eq!(argv, ["ed", "/tmp/$(rm -rf ~)'\""]);
+        }
+    }
+
+    mod exec_program {
+        use super::*;
+
+        #[test]
+        fn strips_directory_and_arguments() {
+            // Arrange
+            let e = DesktopEntry { exec: "\"/opt/My App/bin/app\" --new %U".to_string(), ..Default::default() };
+
+            // Act
+            let program = exec_program(&e);
+
+            // Assert
+            assert_eq!(program.as_deref(), Some("app"));
+        }
+
+        #[test]
+        fn empty_exec_has_no_program() {
+            // Act
+            let program = exec_program(&DesktopEntry::default());
+
+            // Assert
+            assert_eq!(program, None);
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! dmenu-compatible mode: pick from lines read on stdin using the launcher window.
+//!
+//! The chosen line is printed to stdout. Enter exits with 0, the modifier keys
+//! with their own codes so scripts can branch on them (`--exit-code` changes
+//! them), and Escape with 1.
+
+use crate::{name_match_penalty, normalize, Config, SearchResult};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::BufRead;
+
+pub const EXIT_CANCELLED: i32 = 1;
+
+const MODIFIER_EXIT_CODES: &[(&str, i32)] = &[("", 0), ("ctrl", 10), ("shift", 11), ("alt", 12)];
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct DmenuOptions {
+    pub prompt: Option<String>,
+    pub multi: bool,
+    /// `--exit-code` overrides of [`MODIFIER_EXIT_CODES`].
+    #[serde(skip)]
+    pub exit_codes: Vec<(String, i32)>,
+}
+
+impl DmenuOptions {
+    pub fn exit_code(&self, modifier: &str) -> i32 {
+        self.exit_codes.iter()
+            .map(|(name, code)| (name.as_str(), *code))
+            .chain(MODIFIER_EXIT_CODES.iter().copied())
+            .find(|(name, _)| *name == modifier)
+            .map_or(0, |(_, code)| code)
+    }
+}
+
+/// Parses `ctrl=20`; the modifier is one of `enter`, `ctrl`, `shift` or `alt`.
+fn parse_exit_code(value: &str) -> Result<(String, i32), String> {
+    let invalid = || format!("invalid --exit-code {} (expected MODIFIER=CODE, e.g. ctrl=20)", value);
+    let (modifier, code) = value.split_once('=').ok_or_else(invalid)?;
+    let modifier = match modifier.to_lowercase().as_str() {
+        "enter" => String::new(),
+        m @ ("ctrl" | "shift" | "alt") => m.to_string(),
+        _ => return Err(invalid()),
+    };
+    let code = code.parse().ok().filter(|c| (0..=255).contains(c)).ok_or_else(invalid)?;
+    Ok((modifier, code))
+}
+
+/// Parses `--dmenu [--prompt <text>] [--multi] [--exit-code MODIFIER=CODE]...`;
+/// `None` when `--dmenu` is absent.
+pub fn parse_args(args: &[String]) -> Result<Option<DmenuOptions>, String> {
+    if !args.iter().any(|a| a == "--dmenu") {
+        return Ok(None);
+    }
+    let prompt = args.iter().position(|a| a == "--prompt").and_then(|i| args.get(i + 1)).cloned();
+    let exit_codes = args.iter().enumerate()
+        .filter(|(_, a)| *a == "--exit-code")
+        .map(|(i, _)| args.get(i + 1).ok_or("--exit-code needs a value".to_string()).and_then(|v| parse_exit_code(v)))
+        .collect::<Result<_, _>>()?;
+    Ok(Some(DmenuOptions { prompt, multi: args.iter().any(|a| a == "--multi"), exit_codes }))
+}
+
+pub fn read_lines(input: impl BufRead) -> Vec<String> {
+    input.lines()
+        .map_while(Result::ok)
+        .map(|l| l.trim_end_matches('\r').to_string())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+/// Stdin lines with their folded characters, worked out once when read rather than
+/// on every keystroke.
+pub struct Input {
+    pub lines: Vec<String>,
+    folded: Vec<Vec<char>>,
+}
+
+impl Input {
+    pub fn new(lines: Vec<String>, transliterate: bool) -> Self {
+        let folded = lines.iter().map(|l| normalize::fold_with(l, transliterate).chars().collect()).collect();
+        Self { lines, folded }
+    }
+}
+
+/// Characters skipped inside the tightest occurrence of `word` as a subsequence of
+/// `line`, so `fb` scores 0 in `foo/fb` and 2 in `foo/bar`; `None` when it does not occur.
+fn fuzzy_gaps(line: &[char], word: &[char]) -> Option<usize> {
+    let first = *word.first()?;
+    line.iter().enumerate()
+        .filter(|(_, c)| **c == first)
+        .filter_map(|(start, _)| {
+            let mut end = start;
+            for c in &word[1..] {
+                end += 1 + line[end + 1..].iter().position(|l| l == c)?;
+            }
+            Some(end + 1 - start - word.len())
+        })
+        .min()
+}
+
+/// Lines holding the letters of every query word in order, though not necessarily
+/// together. Lines containing the words as typed rank first by name match, then
+/// lines with fewer skipped characters, then input order.
+pub fn filter(input: &Input, query: &str, config: &Config) -> Vec<SearchResult> {
+    let words: Vec<Vec<char>> = normalize::fold_with(query, config.transliterate)
+        .split_whitespace().map(|w| w.chars().collect()).collect();
+    let mut matches: Vec<(&String, usize)> = input.lines.iter().zip(&input.folded)
+        .filter_map(|(line, folded)| {
+            let gaps: Option<Vec<usize>> = words.iter().map(|w| fuzzy_gaps(folded, w)).collect();
+            Some((line, gaps?.iter().sum()))
+        })
+        .collect();
+    if !words.is_empty() {
+        matches.sort_by_cached_key(|(line, gaps)| (name_match_penalty(line, query.trim(), config), *gaps));
+    }
+    matches.into_iter()
+        .map(|(line, _)| SearchResult { path: line.clone(), name: line.clone(), is_dir: false, icon: None, score: None })
+        .collect()
+}
+
+/// Selected lines in input order, each once even when the input repeats it; text typed
+/// without a matching line is printed as-is.
+pub fn selection_output(lines: &[String], selected: &[String]) -> String {
+    let wanted: HashSet<&String> = selected.iter().collect();
+    let mut printed = HashSet::new();
+    lines.iter()
+        .filter(|l| wanted.contains(l))
+        .chain(selected)
+        .filter(|l| printed.insert(*l))
+        .map(|l| format!("{}\n", l))
+        .collect()
+}
+
+pub fn init_script(options: &DmenuOptions) -> String {
+    format!("window.DMENU = {};", serde_json::to_string(options).unwrap_or_else(|_| "{}".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(list: &[&str]) -> Vec<String> {
+        list.iter().map(|l| l.to_string()).collect()
+    }
+
+    mod parse_args {
+        use super::*;
+
+        #[test]
+        fn none_without_flag() {
+            assert_eq!(parse_args(&lines(&["launcher", "--show"])), Ok(None));
+        }
+
+        #[test]
+        fn reads_prompt_and_multi() {
+            // Act
+            let options = parse_args(&lines(&["launcher", "--dmenu", "--prompt", "Branch", "--multi"]));
+
+            // Assert
+            let expected = DmenuOptions { prompt: Some("Branch".to_string()), multi: true, exit_codes: Vec::new() };
+            assert_eq!(options, Ok(Some(expected)));
+        }
+
+        #[test]
+        fn reads_exit_codes() {
+            // Act
+            let options = parse_args(&lines(&["launcher", "--dmenu", "--exit-code", "ctrl=20", "--exit-code", "Enter=3"]));
+
+            // Assert
+            assert_eq!(options.unwrap().unwrap().exit_codes, [("ctrl".to_string(), 20), (String::new(), 3)]);
+        }
+
+        #[test]
+        fn rejects_bad_exit_codes() {
+            for value in ["ctrl", "super=1", "alt=x", "shift=300"] {
+                assert!(parse_args(&lines(&["launcher", "--dmenu", "--exit-code", value])).is_err(), "{}", value);
+            }
+            assert!(parse_args(&lines(&["launcher", "--dmenu", "--exit-code"])).is_err());
+        }
+    }
+
+    mod read_lines {
+        use super::*;
+
+        #[test]
+        fn skips_blank_lines_and_carriage_returns() {
+            let input = "main\r\n\n  feature/x\n";
+            assert_eq!(read_lines(input.as_bytes()), lines(&["main", "  feature/x"]));
+        }
+    }
+
+    mod filter {
+        use super::*;
+
+        fn names(results: Vec<SearchResult>) -> Vec<String> {
+            results.into_iter().map(|r| r.name).collect()
+        }
+
+        fn filter_lines(input: &[String], query: &str, config: &Config) -> Vec<SearchResult> {
+            filter(&Input::new(input.to_vec(), config.transliterate), query, config)
+        }
+
+        #[test]
+        fn empty_query_keeps_input_order() {
+            let input = lines(&["b", "a", "c"]);
+            assert_eq!(names(filter_lines(&input, "", &Config::default())), input);
+        }
+
+        #[test]
+        fn requires_every_word() {
+            // Arrange
+            let input = lines(&["fix login bug", "login page", "fix typo"]);
+
+            // Act
+            let result = filter_lines(&input, "fix login", &Config::default());
+
+            // Assert
+            assert_eq!(names(result), lines(&["fix login bug"]));
+        }
+
+        #[test]
+        fn exact_and_prefix_matches_rank_first() {
+            // Arrange
+            let input = lines(&["feature/main", "main-old", "main"]);
+
+            // Act
+            let result = filter_lines(&input, "main", &Config::default());
+
+            // Assert
+            assert_eq!(names(result), lines(&["main", "main-old", "feature/main"]));
+        }
+
+        #[test]
+        fn is_case_insensitive() {
+            let input = lines(&["README.md"]);
+            assert_eq!(filter_lines(&input, "readme", &Config::default()).len(), 1);
+        }
+
+        #[test]
+        fn matches_letters_in_order() {
+            // Arrange
+            let input = lines(&["feature/login-button", "fix/logging", "docs"]);
+
+            // Act
+            let result = filter_lines(&input, "flb", &Config::default());
+
+            // Assert
+            assert_eq!(names(result), lines(&["feature/login-button"]));
+        }
+
+        #[test]
+        fn tighter_fuzzy_matches_rank_first() {
+            // Arrange
+            let input = lines(&["m-a-i-n", "mxxxxaxxxxixxxxn", "main-ish"]);
+
+            // Act
+            let result = filter_lines(&input, "main", &Config::default());
+
+            // Assert
+            assert_eq!(names(result), lines(&["main-ish", "m-a-i-n", "mxxxxaxxxxixxxxn"]));
+        }
+
+        #[test]
+        fn ignores_accents() {
+            let input = lines(&["Cr\u{e8}me br\u{fb}l\u{e9}e"]);
+            assert_eq!(filter_lines(&input, "creme brulee", &Config::default()).len(), 1);
+        }
+    }
+
+    mod exit_code {
+        use super::*;
+
+        #[test]
+        fn maps_modifiers() {
+            let options = DmenuOptions::default();
+            assert_eq!(options.exit_code(""), 0);
+            assert_eq!(options.exit_code("ctrl"), 10);
+            assert_eq!(options.exit_code("shift"), 11);
+            assert_eq!(options.exit_code("alt"), 12);
+        }
+
+        #[test]
+        fn overrides_win() {
+            // Arrange
+            let options = DmenuOptions { exit_codes: vec![("ctrl".to_string(), 20)], ..DmenuOptions::default() };
+
+            // Assert
+            assert_eq!(options.exit_code("ctrl"), 20);
+            assert_eq!(options.exit_code("alt"), 12);
+        }
+    }
+
+    mod selection_output {
+        use super::*;
+
+        #[test]
+        fn prints_in_input_order() {
+            // Arrange
+            let input = lines(&["a", "b", "c"]);
+
+            // Act
+            let output = selection_output(&input, &lines(&["c", "a"]));
+
+            // Assert
+            assert_eq!(output, "a\nc\n");
+        }
+
+        #[test]
+        fn prints_repeated_lines_once() {
+            // Arrange
+            let input = lines(&["a", "b", "a"]);
+
+            // Act
+            let output = selection_output(&input, &lines(&["a"]));
+
+            // Assert
+            assert_eq!(output, "a\n");
+        }
+
+        #[test]
+        fn prints_typed_text_without_match() {
+            assert_eq!(selection_output(&lines(&["a"]), &lines(&["new-branch"])), "new-branch\n");
+        }
+    }
+}
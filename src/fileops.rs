@@ -0,0 +1,550 @@
+//! File operations on search results: freedesktop.org Trash, rename, duplicate and create.
+
+use std::ffi::OsString;
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Rejects names that would escape the target directory or are otherwise unusable.
+pub fn validate_name(name: &str) -> io::Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\0') {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid name: {:?}", name)));
+    }
+    Ok(())
+}
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", path.display()))
+}
+
+/// Makes `path` absolute without resolving a trailing symlink, so the link itself is trashed.
+fn absolute(path: &Path) -> io::Result<PathBuf> {
+    let name = path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("cannot trash {}", path.display())))?;
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    Ok(fs::canonicalize(parent)?.join(name))
+}
+
+pub(crate) fn local_timestamp(secs: i64) -> String {
+    // SAFETY: localtime_r only writes to the provided tm struct.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let time = secs as libc::time_t;
+    unsafe { libc::localtime_r(&time, &mut tm) };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min, tm.tm_sec
+    )
+}
+
+fn trashinfo(original: &Path, date: &str) -> String {
+    format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        crate::percent_encode_path(&original.to_string_lossy()),
+        date
+    )
+}
+
+fn create_private_dir(path: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(path)
+}
+
+fn mount_point(path: &Path, dev: u64) -> PathBuf {
+    let mut top = path.to_path_buf();
+    for ancestor in path.ancestors().skip(1) {
+        match fs::metadata(ancestor) {
+            Ok(meta) if meta.dev() == dev => top = ancestor.to_path_buf(),
+            _ => break,
+        }
+    }
+    top
+}
+
+/// Picks `$topdir/.Trash/$uid` when the admin-created `.Trash` is a sticky,
+/// non-symlink directory, otherwise `$topdir/.Trash-$uid`.
+fn topdir_trash(topdir: &Path, uid: u32) -> PathBuf {
+    let shared = topdir.join(".Trash");
+    let usable = fs::symlink_metadata(&shared)
+        .map(|m| m.is_dir() && m.mode() & 0o1000 != 0)
+        .unwrap_or(false);
+    if usable {
+        shared.join(uid.to_string())
+    } else {
+        topdir.join(format!(".Trash-{}", uid))
+    }
+}
+
+/// Moves `path` into `trash`, reserving a unique name by creating the `.trashinfo` first.
+fn trash_into(trash: &Path, path: &Path, info_path: &Path, date: &str) -> io::Result<PathBuf> {
+    let (files, info) = (trash.join("files"), trash.join("info"));
+    create_private_dir(&files)?;
+    create_private_dir(&info)?;
+    let base = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    for n in 1.. {
+        let name = if n == 1 { base.clone() } else { format!("{}.{}", base, n) };
+        let info_file = info.join(format!("{}.trashinfo", name));
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&info_file) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        let dest = files.join(&name);
+        if exists(&dest) {
+            let _ = fs::remove_file(&info_file);
+            continue;
+        }
+        let moved = file.write_all(trashinfo(info_path, date).as_bytes())
+            .and_then(|_| fs::rename(path, &dest));
+        if let Err(e) = moved {
+            let _ = fs::remove_file(&info_file);
+            return Err(e);
+        }
+        return Ok(dest);
+    }
+    unreachable!()
+}
+
+/// Moves `path` to the trash on the same filesystem, following the freedesktop.org Trash spec.
+pub fn trash(path: &Path) -> io::Result<PathBuf> {
+    let path = absolute(path)?;
+    let meta = fs::symlink_metadata(&path)?;
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+    let date = local_timestamp(secs);
+
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home data directory"))?;
+    create_private_dir(&data_dir)?;
+    if fs::metadata(&data_dir)?.dev() == meta.dev() {
+        return trash_into(&data_dir.join("Trash"), &path, &path, &date);
+    }
+
+    let topdir = mount_point(&path, meta.dev());
+    // SAFETY: getuid has no preconditions and cannot fail.
+    let uid = unsafe { libc::getuid() };
+    let relative = path.strip_prefix(&topdir).unwrap_or(&path);
+    trash_into(&topdir_trash(&topdir, uid), &path, relative, &date)
+}
+
+/// Whether `err` says the filesystem or kernel has no `RENAME_NOREPLACE`.
+#[cfg(target_os = "linux")]
+fn noreplace_unsupported(err: &io::Error) -> bool {
+    matches!(err.raw_os_error(), Some(libc::EINVAL | libc::ENOSYS | libc::ENOTSUP))
+}
+
+/// Renames `from` to `to`, failing with `AlreadyExists` rather than replacing a file
+/// that appears at `to` in the meantime.
+fn rename_noreplace(from: &Path, to: &Path) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let (from_c, to_c) = (CString::new(from.as_os_str().as_bytes())?, CString::new(to.as_os_str().as_bytes())?);
+        // SAFETY: both paths are NUL-terminated and outlive the call.
+        let rc = unsafe {
+            libc::renameat2(libc::AT_FDCWD, from_c.as_ptr(), libc::AT_FDCWD, to_c.as_ptr(), libc::RENAME_NOREPLACE)
+        };
+        if rc == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if !noreplace_unsupported(&err) {
+            return Err(err);
+        }
+    }
+    rename_by_link(from, to)
+}
+
+/// [`rename_noreplace`] where the kernel cannot: a hard link fails on an existing name.
+fn rename_by_link(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(from)?.is_dir() {
+        // Directories cannot be hard linked. Claim the name with an empty directory,
+        // which rename replaces but a file or non-empty directory made since would not.
+        fs::create_dir(to)?;
+        if let Err(e) = fs::rename(from, to) {
+            let _ = fs::remove_dir(to);
+            return Err(e);
+        }
+        return Ok(());
+    }
+    fs::hard_link(from, to)?;
+    fs::remove_file(from)
+}
+
+pub fn rename(path: &Path, new_name: &str) -> io::Result<PathBuf> {
+    validate_name(new_name)?;
+    let target = path.with_file_name(new_name);
+    match rename_noreplace(path, &target) {
+        Ok(()) => Ok(target),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(already_exists(&target)),
+        Err(e) => Err(e),
+    }
+}
+
+/// `report.pdf` becomes `report (copy).pdf`, then `report (copy 2).pdf`, and so on.
+fn duplicate_name(path: &Path, n: u32) -> OsString {
+    let suffix = if n == 1 { " (copy)".to_string() } else { format!(" (copy {})", n) };
+    let ext = path.extension().filter(|_| !path.is_dir());
+    let base = if ext.is_some() { path.file_stem() } else { path.file_name() };
+    let mut name = base.unwrap_or_default().to_os_string();
+    name.push(suffix);
+    if let Some(ext) = ext {
+        name.push(".");
+        name.push(ext);
+    }
+    name
+}
+
+/// Copies a regular file into a newly created `dst`, failing if `dst` already exists.
+fn copy_file_new(src: &Path, dst: &Path, meta: &fs::Metadata) -> io::Result<()> {
+    let mut from = File::open(src)?;
+    let mut to = OpenOptions::new().write(true).create_new(true).open(dst)?;
+    io::copy(&mut from, &mut to)?;
+    to.set_permissions(meta.permissions())
+}
+
+/// Copies `src` to `dst`, which must not exist yet; fails with `AlreadyExists` if it does.
+fn copy_recursive(src: &Path, dst: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(src)?;
+    if meta.file_type().is_symlink() {
+        return std::os::unix::fs::symlink(fs::read_link(src)?, dst);
+    }
+    if !meta.is_dir() {
+        return copy_file_new(src, dst, &meta);
+    }
+    fs::create_dir(dst)?;
+    fs::set_permissions(dst, meta.permissions())?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        copy_recursive(&entry.path(), &dst.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+pub fn duplicate(path: &Path) -> io::Result<PathBuf> {
+    fs::symlink_metadata(path)?;
+    for n in 1.. {
+        let target = path.with_file_name(duplicate_name(path, n));
+        match copy_recursive(path, &target) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            result => return result.map(|_| target),
+        }
+    }
+    unreachable!()
+}
+
+pub fn create_file(dir: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_name(name)?;
+    let target = dir.join(name);
+    OpenOptions::new().write(true).create_new(true).open(&target)?;
+    Ok(target)
+}
+
+pub fn create_folder(dir: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_name(name)?;
+    let target = dir.join(name);
+    fs::create_dir(&target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    mod validate_name {
+        use super::*;
+
+        #[test]
+        fn accepts_plain_names() {
+            assert!(validate_name("report (final).pdf").is_ok());
+            assert!(validate_name(".hidden").is_ok());
+        }
+
+        #[test]
+        fn rejects_path_traversal() {
+            for name in ["", ".", "..", "a/b", "../x"] {
+                assert!(validate_name(name).is_err(), "{:?} should be rejected", name);
+            }
+        }
+    }
+
+    mod trash {
+        use super::*;
+
+        #[test]
+        fn moves_file_and_writes_trashinfo() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let file = dir.path().join("my file.txt");
+            fs::write(&file, "x").unwrap();
+            let trash = dir.path().join("Trash");
+
+            // Act
+            let dest = trash_into(&trash, &file, &file, "2024-01-02T03:04:05").unwrap();
+
+            // Assert
+            assert!(!file.exists());
+            assert_eq!(dest, trash.join("files/my file.txt"));
+            let info = fs::read_to_string(trash.join("info/my file.txt.trashinfo")).unwrap();
+            let expected_path = crate::percent_encode_path(file.to_str().unwrap());
+            assert_eq!(info, format!("[Trash Info]\nPath={}\nDeletionDate=2024-01-02T03:04:05\n", expected_path));
+            assert!(info.contains("my%20file.txt"));
+        }
+
+        #[test]
+        fn picks_unique_name_on_collision() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let trash = dir.path().join("Trash");
+            let first = dir.path().join("a/x.txt");
+            let second = dir.path().join("b/x.txt");
+            for f in [&first, &second] {
+                fs::create_dir_all(f.parent().unwrap()).unwrap();
+                fs::write(f, "x").unwrap();
+            }
+
+            // Act
+            trash_into(&trash, &first, &first, "d").unwrap();
+            let dest = trash_into(&trash, &second, &second, "d").unwrap();
+
+            // Assert
+            assert_eq!(dest, trash.join("files/x.txt.2"));
+            assert!(trash.join("info/x.txt.2.trashinfo").exists());
+        }
+
+        #[test]
+        fn removes_trashinfo_when_move_fails() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let trash = dir.path().join("Trash");
+            let missing = dir.path().join("missing");
+
+            // Act
+            let result = trash_into(&trash, &missing, &missing, "d");
+
+            // Assert
+            assert!(result.is_err());
+            assert!(!trash.join("info/missing.trashinfo").exists());
+        }
+
+        #[test]
+        fn uses_relative_path_for_topdir_trash() {
+            assert_eq!(
+                trashinfo(Path::new("docs/a b.txt"), "d"),
+                "[Trash Info]\nPath=docs/a%20b.txt\nDeletionDate=d\n"
+            );
+        }
+
+        #[test]
+        fn topdir_falls_back_to_per_user_dir() {
+            let dir = tempdir().unwrap();
+            assert_eq!(topdir_trash(dir.path(), 1000), dir.path().join(".Trash-1000"));
+        }
+
+        #[test]
+        fn topdir_uses_sticky_shared_trash() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let shared = dir.path().join(".Trash");
+            fs::create_dir(&shared).unwrap();
+            fs::set_permissions(&shared, std::os::unix::fs::PermissionsExt::from_mode(0o1777)).unwrap();
+
+            // Act & Assert
+            assert_eq!(topdir_trash(dir.path(), 1000), shared.join("1000"));
+        }
+
+        #[test]
+        fn local_timestamp_has_iso_shape() {
+            let date = local_timestamp(0);
+            assert_eq!(date.len(), 19);
+            assert_eq!(&date[10..11], "T");
+        }
+    }
+
+    mod rename {
+        use super::*;
+
+        #[test]
+        fn renames_in_place() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let file = dir.path().join("old.txt");
+            fs::write(&file, "x").unwrap();
+
+            // Act
+            let target = rename(&file, "new.txt").unwrap();
+
+            // Assert
+            assert_eq!(target, dir.path().join("new.txt"));
+            assert!(target.exists() && !file.exists());
+        }
+
+        #[test]
+        fn refuses_to_overwrite() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+            fs::write(&a, "a").unwrap();
+            fs::write(&b, "b").unwrap();
+
+            // Act
+            let result = rename(&a, "b");
+
+            // Assert
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+            assert_eq!(fs::read_to_string(&b).unwrap(), "b");
+        }
+
+        #[test]
+        fn renames_directories() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let old = dir.path().join("old");
+            fs::create_dir(&old).unwrap();
+            fs::write(old.join("f"), "f").unwrap();
+
+            // Act
+            let target = rename(&old, "new").unwrap();
+
+            // Assert
+            assert_eq!(fs::read_to_string(target.join("f")).unwrap(), "f");
+            assert!(!old.exists());
+        }
+
+        #[test]
+        fn link_fallback_moves_files_and_directories() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let (file, folder) = (dir.path().join("a"), dir.path().join("d"));
+            fs::write(&file, "a").unwrap();
+            fs::create_dir(&folder).unwrap();
+
+            // Act
+            rename_by_link(&file, &dir.path().join("a2")).unwrap();
+            rename_by_link(&folder, &dir.path().join("d2")).unwrap();
+
+            // Assert
+            assert!(!file.exists() && !folder.exists());
+            assert_eq!(fs::read_to_string(dir.path().join("a2")).unwrap(), "a");
+            assert!(dir.path().join("d2").is_dir());
+        }
+
+        #[test]
+        fn link_fallback_refuses_to_overwrite() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let (file, folder, taken) = (dir.path().join("a"), dir.path().join("d"), dir.path().join("b"));
+            fs::write(&file, "a").unwrap();
+            fs::create_dir(&folder).unwrap();
+            fs::write(&taken, "b").unwrap();
+
+            // Act
+            let file_result = rename_by_link(&file, &taken);
+            let folder_result = rename_by_link(&folder, &taken);
+
+            // Assert
+            assert_eq!(file_result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+            assert_eq!(folder_result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+            assert_eq!(fs::read_to_string(&taken).unwrap(), "b");
+            assert!(file.exists() && folder.exists());
+        }
+    }
+
+    mod duplicate {
+        use super::*;
+
+        #[test]
+        fn names_copies_before_extension() {
+            assert_eq!(duplicate_name(Path::new("/x/report.pdf"), 1), "report (copy).pdf");
+            assert_eq!(duplicate_name(Path::new("/x/report.pdf"), 2), "report (copy 2).pdf");
+            assert_eq!(duplicate_name(Path::new("/x/.bashrc"), 1), ".bashrc (copy)");
+        }
+
+        #[test]
+        fn keeps_non_utf8_names() {
+            // Arrange
+            use std::os::unix::ffi::OsStrExt;
+            let path = Path::new(std::ffi::OsStr::from_bytes(b"/x/caf\xe9.txt"));
+
+            // Act
+            let name = duplicate_name(path, 1);
+
+            // Assert
+            assert_eq!(name.as_bytes(), b"caf\xe9 (copy).txt");
+        }
+
+        #[test]
+        fn keeps_permissions_of_copied_files() {
+            // Arrange
+            use std::os::unix::fs::PermissionsExt;
+            let dir = tempdir().unwrap();
+            let file = dir.path().join("run.sh");
+            fs::write(&file, "#!/bin/sh").unwrap();
+            fs::set_permissions(&file, fs::Permissions::from_mode(0o755)).unwrap();
+
+            // Act
+            let copy = duplicate(&file).unwrap();
+
+            // Assert
+            assert_eq!(fs::metadata(copy).unwrap().permissions().mode() & 0o777, 0o755);
+        }
+
+        #[test]
+        fn copies_file_with_next_free_name() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let file = dir.path().join("a.txt");
+            fs::write(&file, "content").unwrap();
+            fs::write(dir.path().join("a (copy).txt"), "").unwrap();
+
+            // Act
+            let copy = duplicate(&file).unwrap();
+
+            // Assert
+            assert_eq!(copy, dir.path().join("a (copy 2).txt"));
+            assert_eq!(fs::read_to_string(copy).unwrap(), "content");
+        }
+
+        #[test]
+        fn copies_directories_recursively() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let src = dir.path().join("proj.v1");
+            fs::create_dir_all(src.join("sub")).unwrap();
+            fs::write(src.join("sub/f"), "f").unwrap();
+
+            // Act
+            let copy = duplicate(&src).unwrap();
+
+            // Assert
+            assert_eq!(copy, dir.path().join("proj.v1 (copy)"));
+            assert_eq!(fs::read_to_string(copy.join("sub/f")).unwrap(), "f");
+        }
+    }
+
+    mod create {
+        use super::*;
+
+        #[test]
+        fn creates_file_and_folder() {
+            let dir = tempdir().unwrap();
+            assert!(create_file(dir.path(), "notes.md").unwrap().is_file());
+            assert!(create_folder(dir.path(), "sub").unwrap().is_dir());
+        }
+
+        #[test]
+        fn refuses_existing_names() {
+            let dir = tempdir().unwrap();
+            fs::write(dir.path().join("a"), "keep").unwrap();
+            assert!(create_file(dir.path(), "a").is_err());
+            assert!(create_folder(dir.path(), "a").is_err());
+            assert_eq!(fs::read_to_string(dir.path().join("a")).unwrap(), "keep");
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Git ignore rules for search results inside working trees.
+//!
+//! A path is checked against the `.ignore` and `.gitignore` files of every directory
+//! from its own up to the repository root (deeper files win), then
+//! `.git/info/exclude`, then the global excludes file from git's config. Parsed
+//! files are cached per directory and dropped after [`CACHE_TTL`], so edits to an
+//! ignore file show up on a later search.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Matchers for one working tree.
+struct Repo {
+    info_exclude: Gitignore,
+    global: Gitignore,
+}
+
+pub struct IgnoreCache {
+    use_global: bool,
+    created: Instant,
+    /// Directory to the root of the working tree containing it, if any.
+    roots: HashMap<PathBuf, Option<PathBuf>>,
+    repos: HashMap<PathBuf, Arc<Repo>>,
+    /// `.gitignore` and `.ignore` of one directory, rooted there.
+    dirs: HashMap<PathBuf, Arc<Gitignore>>,
+}
+
+impl IgnoreCache {
+    pub fn new() -> Self {
+        Self::with_global(true)
+    }
+
+    /// `use_global: false` skips the user's global excludes file.
+    pub fn with_global(use_global: bool) -> Self {
+        Self {
+            use_global,
+            created: Instant::now(),
+            roots: HashMap::new(),
+            repos: HashMap::new(),
+            dirs: HashMap::new(),
+        }
+    }
+
+    fn repo_root(&mut self, dir: &Path) -> Option<PathBuf> {
+        if let Some(root) = self.roots.get(dir) {
+            return root.clone();
+        }
+        let root = if dir.join(".git").exists() {
+            Some(dir.to_path_buf())
+        } else {
+            dir.parent().and_then(|parent| self.repo_root(parent))
+        };
+        self.roots.insert(dir.to_path_buf(), root.clone());
+        root
+    }
+
+    fn repo(&mut self, root: &Path) -> Arc<Repo> {
+        let use_global = self.use_global;
+        self.repos.entry(root.to_path_buf()).or_insert_with(|| {
+            let mut exclude = GitignoreBuilder::new(root);
+            exclude.add(root.join(".git/info/exclude"));
+            let global = if use_global {
+                GitignoreBuilder::new(root).build_global().0
+            } else {
+                Gitignore::empty()
+            };
+            Arc::new(Repo { info_exclude: exclude.build().unwrap_or_else(|_| Gitignore::empty()), global })
+        }).clone()
+    }
+
+    fn dir_rules(&mut self, dir: &Path) -> Arc<Gitignore> {
+        self.dirs.entry(dir.to_path_buf()).or_insert_with(|| {
+            let mut builder = GitignoreBuilder::new(dir);
+            // Later files take precedence, so `.ignore` overrides `.gitignore`.
+            for name in [".gitignore", ".ignore"] {
+                let file = dir.join(name);
+                if file.is_file() {
+                    builder.add(file);
+                }
+            }
+            Arc::new(builder.build().unwrap_or_else(|_| Gitignore::empty()))
+        }).clone()
+    }
+
+    /// True when `path` is inside a git working tree and ignored there.
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Some(parent) = path.parent() else { return false };
+        let Some(root) = self.repo_root(parent) else { return false };
+        if path.components().any(|c| c.as_os_str() == ".git") {
+            return true;
+        }
+
+        let verdict = |rules: &Gitignore| match rules.matched_path_or_any_parents(path, is_dir) {
+            Match::Ignore(_) => Some(true),
+            Match::Whitelist(_) => Some(false),
+            Match::None => None,
+        };
+        for dir in parent.ancestors().take_while(|dir| dir.starts_with(&root)) {
+            if let Some(ignored) = verdict(&self.dir_rules(dir)) {
+                return ignored;
+            }
+        }
+        let repo = self.repo(&root);
+        verdict(&repo.info_exclude).or_else(|| verdict(&repo.global)).unwrap_or(false)
+    }
+}
+
+impl Default for IgnoreCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks `path` against the shared cache.
+pub fn is_ignored(path: &str) -> bool {
+    static CACHE: OnceLock<Mutex<IgnoreCache>> = OnceLock::new();
+    let mut cache = CACHE.get_or_init(|| Mutex::new(IgnoreCache::new())).lock().unwrap();
+    if cache.created.elapsed() > CACHE_TTL {
+        *cache = IgnoreCache::new();
+    }
+    let path = Path::new(path);
+    cache.is_ignored(path, path.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::{tempdir, TempDir};
+
+    fn repo(files: &[(&str, &str)]) -> TempDir {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    mod is_ignored {
+        use super::*;
+
+        #[test]
+        fn applies_root_gitignore() {
+            // Arrange
+            let dir = repo(&[(".gitignore", "build/\n*.log\n")]);
+            let mut cache = IgnoreCache::with_global(false);
+
+            // Assert
+            assert!(cache.is_ignored(&dir.path().join("build/out/app.js"), false));
+            assert!(cache.is_ignored(&dir.path().join("src/debug.log"), false));
+            assert!(!cache.is_ignored(&dir.path().join("src/main.rs"), false));
+        }
+
+        #[test]
+        fn nested_files_override_parents() {
+            // Arrange
+            let dir = repo(&[(".gitignore", "*.log\n"), ("keep/.gitignore", "!important.log\n")]);
+            let mut cache = IgnoreCache::with_global(false);
+
+            // Assert
+            assert!(!cache.is_ignored(&dir.path().join("keep/important.log"), false));
+            assert!(cache.is_ignored(&dir.path().join("keep/other.log"), false));
+        }
+
+        #[test]
+        fn dot_ignore_overrides_gitignore() {
+            // Arrange
+            let dir = repo(&[(".gitignore", "vendor/\n"), (".ignore", "!vendor/\nsecrets.txt\n")]);
+            let mut cache = IgnoreCache::with_global(false);
+
+            // Assert
+            assert!(!cache.is_ignored(&dir.path().join("vendor/lib.rs"), false));
+            assert!(cache.is_ignored(&dir.path().join("secrets.txt"), false));
+        }
+
+        #[test]
+        fn reads_info_exclude() {
+            let dir = repo(&[(".git/info/exclude", "scratch.md\n")]);
+            let mut cache = IgnoreCache::with_global(false);
+            assert!(cache.is_ignored(&dir.path().join("scratch.md"), false));
+        }
+
+        #[test]
+        fn git_directory_is_ignored() {
+            let dir = repo(&[]);
+            let mut cache = IgnoreCache::with_global(false);
+            assert!(cache.is_ignored(&dir.path().join(".git/config"), false));
+        }
+
+        #[test]
+        fn outside_a_repo_nothing_is_ignored() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            fs::write(dir.path().join(".gitignore"), "*\n").unwrap();
+            let mut cache = IgnoreCache::with_global(false);
+
+            // Assert
+            assert!(!cache.is_ignored(&dir.path().join("notes.md"), false));
+        }
+    }
+}
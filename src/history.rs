@@ -0,0 +1,100 @@
+//! Picks made in the launcher window: the query, the results it showed and the one
+//! opened. `launcher --tune` replays them to fit ranking weights.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Older selections are dropped beyond this many.
+const MAX_SELECTIONS: usize = 1000;
+/// Results kept per selection besides the chosen one.
+pub const MAX_CANDIDATES: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub path: String,
+    pub name: String,
+    #[serde(default)]
+    pub is_dir: bool,
+    /// Decayed use count when the results were shown, so replays ignore later uses.
+    pub uses: f64,
+    /// Whether git ignored the path when the results were shown.
+    #[serde(default)]
+    pub ignored: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Selection {
+    pub query: String,
+    pub chosen: String,
+    pub candidates: Vec<Candidate>,
+    pub time: u64,
+}
+
+pub fn history_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("qol-launcher-selections.json")
+}
+
+/// Recorded selections, oldest first; empty when the file is missing or unreadable.
+pub fn load(path: &Path) -> Vec<Selection> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn append(path: &Path, selection: Selection) -> std::io::Result<()> {
+    let mut history = load(path);
+    history.push(selection);
+    let excess = history.len().saturating_sub(MAX_SELECTIONS);
+    history.drain(..excess);
+    fs::write(path, serde_json::to_string(&history)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn selection(query: &str) -> Selection {
+        Selection { query: query.to_string(), chosen: "/a".to_string(), candidates: Vec::new(), time: 0 }
+    }
+
+    mod append {
+        use super::*;
+
+        #[test]
+        fn keeps_order_and_round_trips() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("selections.json");
+
+            // Act
+            append(&path, selection("one")).unwrap();
+            append(&path, selection("two")).unwrap();
+
+            // Assert
+            assert_eq!(load(&path), vec![selection("one"), selection("two")]);
+        }
+
+        #[test]
+        fn drops_oldest_beyond_limit() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("selections.json");
+            let full: Vec<Selection> = (0..MAX_SELECTIONS).map(|i| selection(&i.to_string())).collect();
+            fs::write(&path, serde_json::to_string(&full).unwrap()).unwrap();
+
+            // Act
+            append(&path, selection("new")).unwrap();
+
+            // Assert
+            let history = load(&path);
+            assert_eq!(history.len(), MAX_SELECTIONS);
+            assert_eq!(history[0].query, "1");
+            assert_eq!(history.last().unwrap().query, "new");
+        }
+    }
+}
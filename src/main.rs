@@ -1,60 +1,46 @@
+mod apps;
+mod config;
+mod desktop;
+mod dmenu;
+mod fileops;
+mod gitignore;
+mod history;
+mod normalize;
+mod protocol;
+mod query;
+mod scope;
+mod tune;
+
+use config::{Config, CustomAction, GitIgnored, TerminalConfig};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::io::{Read as IoRead, Write as IoWrite};
-use std::os::unix::net::{UnixListener, UnixStream};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Write as IoWrite};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tao::event::{Event, WindowEvent};
 use tao::event_loop::{ControlFlow, EventLoopBuilder};
 use tao::window::WindowBuilder;
 use wry::http::Request;
 use wry::WebViewBuilder;
 
-const SOCKET_PATH: &str = "/tmp/qol-launcher.sock";
-
-#[derive(Serialize, Deserialize, Clone)]
-struct Config {
-    half_life_days: f64,
-    frequency_bonus: i32,
-    prefer_apps: bool,
-    penalize_hidden: bool,
-    depth_penalty: i32,
-    exact_bonus: i32,
-    prefix_penalty: i32,
-    contains_penalty: i32,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            half_life_days: 7.0,
-            frequency_bonus: 500,
-            prefer_apps: true,
-            penalize_hidden: true,
-            depth_penalty: 2,
-            exact_bonus: 0,
-            prefix_penalty: 100,
-            contains_penalty: 200,
-        }
-    }
-}
-
-fn get_config_path() -> PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("qol-tray/plugins/plugin-launcher/config.json")
-}
-
-fn load_config() -> Config {
-    fs::read_to_string(get_config_path())
-        .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
-        .unwrap_or_default()
-}
+const SOCKET_REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+const QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Backends return this many candidates so scope filtering still leaves enough to rank.
+const BACKEND_LIMIT: usize = 500;
+const MAX_RESULTS: usize = 50;
+/// Paths start here; the default boosts bring the standard application dirs back to 0.
+const BASE_PATH_PENALTY: i32 = 50;
+/// Query word found only in a directory name: between a name hit and a miss.
+const DIR_MATCH_PENALTY: i32 = 250;
+const NO_MATCH_PENALTY: i32 = 300;
+/// Ranks files a git working tree ignores below everything else.
+const IGNORED_PENALTY: i32 = 2000;
 
 #[derive(Serialize, Deserialize, Clone, Default)]
 struct FrequencyEntry {
@@ -114,70 +100,278 @@ fn effective_count(entry: &FrequencyEntry, now: u64, half_life_days: f64) -> f64
 
 #[derive(Debug)]
 enum UserEvent {
-    SearchComplete(Vec<SearchResult>),
-    Show,
+    /// Results and, when the backends could not search, a note saying why.
+    SearchComplete(Vec<SearchResult>, Option<&'static str>),
+    ActionsListed(Vec<ActionInfo>),
+    AppsListed(Vec<AppInfo>),
+    ActionFinished(Result<(), ActionError>),
+    Show(protocol::ShowOptions),
+    /// `config.json` changed on disk and has been reloaded.
+    ConfigChanged,
+    /// Socket command that needs the window; the reply goes back to the socket thread.
+    Socket(protocol::Command, mpsc::Sender<Result<serde_json::Value, String>>),
 }
 
-fn handle_socket_message(stream: &mut UnixStream, proxy: &tao::event_loop::EventLoopProxy<UserEvent>) {
-    let mut buf = [0u8; 16];
-    let Ok(n) = stream.read(&mut buf) else { return };
-    match &buf[..n] {
-        b"show" => { let _ = proxy.send_event(UserEvent::Show); }
-        b"kill" => std::process::exit(0),
-        _ => {}
+fn run_socket_command(
+    command: protocol::Command,
+    proxy: &tao::event_loop::EventLoopProxy<UserEvent>,
+    plugin_dir: &Path,
+) -> Result<serde_json::Value, String> {
+    match command {
+        protocol::Command::Query { query, limit, mode, explain } => {
+            let results = trim_query_results(search(&query, plugin_dir, mode), limit, explain);
+            serde_json::to_value(results).map_err(|e| e.to_string())
+        }
+        protocol::Command::Version => Ok(serde_json::Value::from(env!("CARGO_PKG_VERSION"))),
+        protocol::Command::Kill => Ok(serde_json::Value::Null),
+        protocol::Command::Execute { path, action } if !CLIPBOARD_ACTIONS.contains(&action.as_str()) => {
+            execute_action(&path, &action, &config::current()).map_err(|e| {
+                log_action_error(&path, &e);
+                e.to_string()
+            })?;
+            Ok(serde_json::Value::Null)
+        }
+        command => {
+            let (reply, response) = mpsc::channel();
+            proxy.send_event(UserEvent::Socket(command, reply)).map_err(|_| "event loop has stopped".to_string())?;
+            response.recv_timeout(SOCKET_REPLY_TIMEOUT).map_err(|_| "launcher did not respond".to_string())?
+        }
     }
 }
 
-fn send_socket_command(cmd: &[u8]) -> bool {
-    if let Ok(mut stream) = UnixStream::connect(SOCKET_PATH) {
-        let _ = stream.write_all(cmd);
-        return true;
+fn handle_socket_client(stream: UnixStream, proxy: &tao::event_loop::EventLoopProxy<UserEvent>, plugin_dir: &Path) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (response, kill) = match protocol::parse_request(&line) {
+            Ok(request) => {
+                let kill = request.command == protocol::Command::Kill;
+                let response = match run_socket_command(request.command, proxy, plugin_dir) {
+                    Ok(result) => protocol::Response::success(request.id, result),
+                    Err(e) => protocol::Response::failure(request.id, e),
+                };
+                (response, kill)
+            }
+            Err(e) => (protocol::Response::failure(protocol::request_id(&line), e), false),
+        };
+        let _ = writer.write_all(protocol::encode(&response).as_bytes());
+        if kill {
+            std::process::exit(0);
+        }
     }
-    false
 }
 
-fn start_socket_listener(proxy: tao::event_loop::EventLoopProxy<UserEvent>) -> bool {
-    if send_socket_command(b"show") {
-        return false;
+fn send_socket_command(command: protocol::Command) -> std::io::Result<protocol::Response> {
+    protocol::send(&protocol::socket_path(), command, SOCKET_REPLY_TIMEOUT)
+}
+
+/// Forwards `request` to the running daemon, waiting briefly for one that is still starting up.
+fn notify_running_daemon(request: &protocol::Command) {
+    for _ in 0..20 {
+        match send_socket_command(request.clone()) {
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused) => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            _ => return,
+        }
     }
+}
 
-    let _ = fs::remove_file(SOCKET_PATH);
-    let Ok(listener) = UnixListener::bind(SOCKET_PATH) else { return false };
+fn start_socket_listener(
+    proxy: tao::event_loop::EventLoopProxy<UserEvent>,
+    plugin_dir: PathBuf,
+    request: &protocol::Command,
+) -> bool {
+    let socket = protocol::socket_path();
+    let lock = match protocol::lock_daemon(&socket) {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
+            notify_running_daemon(request);
+            return false;
+        }
+        Err(e) => {
+            eprintln!("launcher: cannot lock {}: {}", socket.display(), e);
+            return false;
+        }
+    };
+    let listener = match protocol::bind(&socket) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("launcher: cannot listen on {}: {}", socket.display(), e);
+            return false;
+        }
+    };
     let _ = listener.set_nonblocking(true);
 
-    std::thread::spawn(move || loop {
-        let Ok((mut stream, _)) = listener.accept() else {
-            std::thread::sleep(std::time::Duration::from_millis(50));
-            continue;
-        };
-        handle_socket_message(&mut stream, &proxy);
+    std::thread::spawn(move || {
+        let _lock = lock;
+        accept_socket_clients(listener, proxy, plugin_dir);
     });
 
     true
 }
 
+/// Serves each connection on its own thread, dropping peers running as another user.
+fn accept_socket_clients(
+    listener: std::os::unix::net::UnixListener,
+    proxy: tao::event_loop::EventLoopProxy<UserEvent>,
+    plugin_dir: PathBuf,
+) {
+    let uid = protocol::current_uid();
+    loop {
+        let Ok((stream, _)) = listener.accept() else {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            continue;
+        };
+        if protocol::peer_uid(&stream).ok() != Some(uid) {
+            continue;
+        }
+        let _ = stream.set_nonblocking(false);
+        let (proxy, plugin_dir) = (proxy.clone(), plugin_dir.clone());
+        std::thread::spawn(move || handle_socket_client(stream, &proxy, &plugin_dir));
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(tag = "type")]
 enum IpcMessage {
     #[serde(rename = "search")]
-    Search { query: String },
+    Search {
+        query: String,
+        #[serde(default)]
+        mode: protocol::SearchMode,
+    },
     #[serde(rename = "execute")]
     Execute { path: String, action: String },
+    #[serde(rename = "list_actions")]
+    ListActions { path: String },
+    #[serde(rename = "list_apps")]
+    ListApps { path: String },
+    #[serde(rename = "file_op")]
+    FileOp {
+        path: String,
+        op: String,
+        #[serde(default)]
+        name: String,
+    },
+    #[serde(rename = "open_with")]
+    OpenWith {
+        path: String,
+        app: String,
+        #[serde(default)]
+        set_default: bool,
+    },
+    #[serde(rename = "dmenu_select")]
+    DmenuSelect {
+        lines: Vec<String>,
+        #[serde(default)]
+        modifier: String,
+    },
     #[serde(rename = "close")]
     Close,
 }
 
-#[derive(Serialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 struct SearchResult {
     path: String,
     name: String,
     is_dir: bool,
     icon: Option<String>,
+    /// How the result was ranked; sent to the window and to `--explain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    score: Option<ScoreBreakdown>,
+}
+
+/// The terms [`score_result`] adds up; a lower `total` ranks higher.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+struct ScoreBreakdown {
+    match_penalty: i32,
+    type_penalty: i32,
+    path_penalty: i32,
+    length_penalty: i32,
+    frequency_bonus: i32,
+    total: i32,
+}
+
+impl std::fmt::Display for ScoreBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} = match {} + type {} + path {} + length {} - frequency {}",
+            self.total, self.match_penalty, self.type_penalty, self.path_penalty, self.length_penalty, self.frequency_bonus,
+        )
+    }
 }
 
 #[derive(Default)]
 struct AppState {
     should_exit: bool,
+    /// Window searches started so far; each search is tagged with its number.
+    searches_started: u64,
+    /// Newest finished window search: its number, query and results, saved with the pick
+    /// for `launcher --tune`.
+    last_search: Option<(u64, String, Vec<SearchResult>)>,
+}
+
+impl AppState {
+    /// Number for a search starting now.
+    fn start_search(&mut self) -> u64 {
+        self.searches_started += 1;
+        self.searches_started
+    }
+
+    /// Keeps a finished search unless one started after it has already finished; returns
+    /// whether it was kept, as only then are its results still worth showing.
+    fn finish_search(&mut self, number: u64, query: String, results: Vec<SearchResult>) -> bool {
+        if self.last_search.as_ref().is_some_and(|(newest, _, _)| *newest > number) {
+            return false;
+        }
+        self.last_search = Some((number, query, results));
+        true
+    }
+}
+
+/// How long after the window hid itself a toggle still counts as closing it. Pressing the
+/// hotkey can move focus away first, hiding the window before the toggle arrives.
+const TOGGLE_GRACE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ToggleAction {
+    Show,
+    Hide,
+    Stay,
+}
+
+#[derive(Debug, Default)]
+struct Visibility {
+    visible: bool,
+    hidden_at: Option<Instant>,
+}
+
+impl Visibility {
+    fn shown(&mut self) {
+        self.visible = true;
+    }
+
+    fn hidden(&mut self, now: Instant) {
+        if self.visible {
+            self.visible = false;
+            self.hidden_at = Some(now);
+        }
+    }
+
+    fn toggle(&self, now: Instant) -> ToggleAction {
+        if self.visible {
+            return ToggleAction::Hide;
+        }
+        match self.hidden_at {
+            Some(at) if now.duration_since(at) < TOGGLE_GRACE => ToggleAction::Stay,
+            _ => ToggleAction::Show,
+        }
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -314,7 +508,40 @@ fn parse_search_result(line: &str) -> SearchResult {
     } else {
         (extract_filename(&path), None)
     };
-    SearchResult { path, name, is_dir, icon }
+    SearchResult { path, name, is_dir, icon, score: None }
+}
+
+/// [`query::ParsedQuery::backend_note`] for a search that came back empty.
+fn search_note(query: &str, results: &[SearchResult]) -> Option<&'static str> {
+    if !results.is_empty() {
+        return None;
+    }
+    let config = config::current();
+    query::parse(config.for_query(query).1).backend_note()
+}
+
+/// Whether the app index is consulted: with no words it would match every application,
+/// so that only happens when the query asks for applications with `type:app` or `app:`.
+fn wants_apps(parsed: &query::ParsedQuery, mode: protocol::SearchMode) -> bool {
+    mode != protocol::SearchMode::Files && (!parsed.text.is_empty() || parsed.kind == Some(query::Kind::App))
+}
+
+/// An application from [`apps`]; its icon is filled in by [`with_app_icons`] once ranking
+/// has trimmed the list, since resolving it probes hundreds of theme paths.
+fn app_result(entry: &desktop::DesktopEntry) -> SearchResult {
+    let path = entry.path.to_string_lossy().to_string();
+    let name = if entry.name.is_empty() { extract_filename(&path) } else { entry.name.clone() };
+    SearchResult { path, name, is_dir: false, icon: None, score: None }
+}
+
+fn with_app_icons(mut results: Vec<SearchResult>, apps: &[desktop::DesktopEntry]) -> Vec<SearchResult> {
+    for result in &mut results {
+        let Some(app) = apps.iter().find(|a| a.path == Path::new(&result.path)) else { continue };
+        result.icon = app.icon.as_deref()
+            .and_then(resolve_icon_path)
+            .and_then(|p| icon_to_data_url(&p));
+    }
+    results
 }
 
 fn extract_filename(path: &str) -> String {
@@ -324,15 +551,21 @@ fn extract_filename(path: &str) -> String {
         .unwrap_or_else(|| path.to_string())
 }
 
-fn search(query: &str, plugin_dir: &std::path::Path) -> Vec<SearchResult> {
+fn search(query: &str, plugin_dir: &std::path::Path, mode: protocol::SearchMode) -> Vec<SearchResult> {
     let script = plugin_dir.join(get_backend_script());
-    let config = load_config();
+    let (config, query) = config::current().for_query(query);
+    let parsed = query::parse(query);
+    if parsed.text.is_empty() && !parsed.has_filters() {
+        return vec![];
+    }
 
     #[cfg(target_os = "windows")]
     let output = Command::new("powershell")
         .args(["-ExecutionPolicy", "Bypass", "-File"])
         .arg(&script)
-        .arg(query)
+        .arg(&parsed.text)
+        .env("LAUNCHER_LIMIT", BACKEND_LIMIT.to_string())
+        .envs(parsed.backend_env())
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .output();
@@ -340,16 +573,40 @@ fn search(query: &str, plugin_dir: &std::path::Path) -> Vec<SearchResult> {
     #[cfg(not(target_os = "windows"))]
     let output = Command::new("bash")
         .arg(&script)
-        .arg(query)
+        .arg(&parsed.text)
+        .env("LAUNCHER_LIMIT", BACKEND_LIMIT.to_string())
+        .envs(parsed.backend_env())
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .output();
 
     let Ok(out) = output else { return vec![] };
     let stdout = String::from_utf8_lossy(&out.stdout);
-    let results: Vec<_> = stdout.lines().filter(|l| !l.is_empty()).map(parse_search_result).collect();
-    let freq = load_frequency();
-    sort_by_relevance(dedupe_results(results), query, &freq, &config)
+    let filter = scope::PathFilter::new(&config.scope);
+    let hide_ignored = config.scope.gitignored == GitIgnored::Hide;
+    // Applications go first so deduplication keeps them over a backend's copy.
+    let apps = if wants_apps(&parsed, mode) { apps::search(&parsed.text, config.transliterate) } else { Vec::new() };
+    let results: Vec<_> = apps.iter().map(app_result)
+        .chain(stdout.lines()
+            .filter(|l| !l.is_empty() && mode.accepts(l) && filter.allows(l))
+            .filter(|l| !(hide_ignored && gitignore::is_ignored(l)))
+            .map(parse_search_result))
+        .collect();
+    with_app_icons(rank_results(results, &parsed, &load_frequency(), &config, SystemTime::now()), &apps)
+}
+
+/// Drops results the query's operators and match modes reject, then sorts and trims.
+fn rank_results(
+    results: Vec<SearchResult>,
+    parsed: &query::ParsedQuery,
+    freq: &FrequencyData,
+    config: &Config,
+    now: SystemTime,
+) -> Vec<SearchResult> {
+    let results = results.into_iter().filter(|r| parsed.matches(r, now)).collect();
+    let mut results = sort_by_relevance(dedupe_results(results), &parsed.text, freq, config);
+    results.truncate(MAX_RESULTS);
+    results
 }
 
 fn dedupe_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
@@ -364,41 +621,98 @@ fn dedupe_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
     }).collect()
 }
 
-fn score_result(r: &SearchResult, query: &str, freq: &FrequencyData, config: &Config) -> i32 {
-    let name = r.name.to_lowercase();
-    let q = query.to_lowercase();
+/// Tier of `term` against an already folded name; `None` when it does not occur.
+fn name_tier(name: &str, term: &str, config: &Config) -> Option<i32> {
+    if name == term { Some(config.exact_bonus) }
+        else if name.starts_with(term) { Some(config.prefix_penalty) }
+        else if name.contains(term) { Some(config.contains_penalty) }
+        else { None }
+}
+
+/// Scores `query` against a name and the directories above it. Each word gets the
+/// tier of its best hit in the name, [`DIR_MATCH_PENALTY`] when it only occurs in a
+/// directory, or [`NO_MATCH_PENALTY`]; the words are averaged, so names holding more
+/// of the words rank higher. The whole query matching the name counts when better.
+/// Name and query are compared after [`normalize::fold_with`]; `dirs` must be folded.
+fn match_penalty(name: &str, dirs: &[String], query: &str, config: &Config) -> i32 {
+    let name = normalize::fold_with(name, config.transliterate);
+    let q = normalize::fold_with(query, config.transliterate);
+    let words: Vec<&str> = q.split_whitespace().collect();
+    let word_tier = |word: &str| name_tier(&name, word, config).unwrap_or_else(|| {
+        if dirs.iter().any(|dir| dir.contains(word)) { DIR_MATCH_PENALTY } else { NO_MATCH_PENALTY }
+    });
+    let q = words.join(" ");
+    if words.len() <= 1 {
+        return word_tier(&q);
+    }
+    let average = words.iter().map(|word| word_tier(word)).sum::<i32>() / words.len() as i32;
+    name_tier(&name, &q, config).map_or(average, |whole| whole.min(average))
+}
+
+fn name_match_penalty(name: &str, query: &str, config: &Config) -> i32 {
+    match_penalty(name, &[], query, config)
+}
+
+/// Folded directory names above the file, for [`match_penalty`].
+fn parent_segments(path: &str, config: &Config) -> Vec<String> {
+    let path = std::path::Path::new(path);
+    path.parent()
+        .map(|dir| dir.components()
+            .map(|c| normalize::fold_with(&c.as_os_str().to_string_lossy(), config.transliterate))
+            .collect())
+        .unwrap_or_default()
+}
+
+fn score_result(r: &SearchResult, query: &str, freq: &FrequencyData, config: &Config) -> ScoreBreakdown {
+    score_terms(r, query, calc_frequency_bonus(&r.path, freq, config), takes_ignored_penalty(&r.path, config), config)
+}
+
+/// Each term of the score, with the frequency bonus and whether the path takes
+/// [`IGNORED_PENALTY`] already known, as when replaying history.
+fn score_terms(r: &SearchResult, query: &str, frequency_bonus: i32, ignored: bool, config: &Config) -> ScoreBreakdown {
     let path = &r.path;
 
-    let match_penalty = if name == q { config.exact_bonus }
-        else if name.starts_with(&q) { config.prefix_penalty }
-        else if name.contains(&q) { config.contains_penalty }
-        else { 300 };
+    let match_penalty = match_penalty(&r.name, &parent_segments(path, config), query, config);
 
     let type_penalty = if !config.prefer_apps || path.ends_with(".desktop") { 0 } else { 1000 };
 
-    let path_penalty = score_path_quality(path, config);
+    let path_penalty = score_path_quality(path, ignored, config);
 
     let length_penalty = r.name.len() as i32;
 
-    let frequency_bonus = calc_frequency_bonus(path, freq, config);
+    ScoreBreakdown {
+        match_penalty,
+        type_penalty,
+        path_penalty,
+        length_penalty,
+        frequency_bonus,
+        total: match_penalty + type_penalty + path_penalty + length_penalty - frequency_bonus,
+    }
+}
+
+fn effective_uses(path: &str, freq: &FrequencyData, config: &Config) -> f64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    freq.entries.get(path).map_or(0.0, |e| effective_count(e, now, config.half_life_days))
+}
 
-    match_penalty + type_penalty + path_penalty + length_penalty - frequency_bonus
+fn frequency_bonus(uses: f64, config: &Config) -> i32 {
+    (uses * config.frequency_bonus as f64) as i32
 }
 
 fn calc_frequency_bonus(path: &str, freq: &FrequencyData, config: &Config) -> i32 {
-    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-    freq.entries.get(path)
-        .map(|e| (effective_count(e, now, config.half_life_days) * config.frequency_bonus as f64) as i32)
-        .unwrap_or(0)
+    frequency_bonus(effective_uses(path, freq, config), config)
+}
+
+/// Whether `path` is git-ignored and the config ranks such files last.
+fn takes_ignored_penalty(path: &str, config: &Config) -> bool {
+    config.scope.gitignored == GitIgnored::Penalize && gitignore::is_ignored(path)
 }
 
-fn score_path_quality(path: &str, config: &Config) -> i32 {
-    let mut penalty = 0i32;
+fn score_path_quality(path: &str, ignored: bool, config: &Config) -> i32 {
+    let mut penalty = BASE_PATH_PENALTY - scope::root_boost(path, &config.scope);
 
-    let standard_dirs = ["/usr/share/applications", "/usr/lib", ".local/share/applications"];
-    let is_standard = standard_dirs.iter().any(|d| path.contains(d));
-    if !is_standard {
-        penalty += 50;
+    if ignored {
+        penalty += IGNORED_PENALTY;
     }
 
     if path.contains("/autostart/") || path.contains("/xdg/") {
@@ -419,7 +733,10 @@ fn score_path_quality(path: &str, config: &Config) -> i32 {
 }
 
 fn sort_by_relevance(mut results: Vec<SearchResult>, query: &str, freq: &FrequencyData, config: &Config) -> Vec<SearchResult> {
-    results.sort_by_key(|r| score_result(r, query, freq, config));
+    for r in &mut results {
+        r.score = Some(score_result(r, query, freq, config));
+    }
+    results.sort_by_key(|r| r.score.map_or(0, |score| score.total));
     results
 }
 
@@ -428,8 +745,8 @@ fn extract_app_id(path: &str) -> String {
         .file_stem()
         .map(|s| s.to_string_lossy().to_string())
         .unwrap_or_default();
-    let after_dots = stem.split('.').last().unwrap_or(&stem);
-    after_dots.split('_').last().unwrap_or(after_dots).to_lowercase()
+    let after_dots = stem.rsplit('.').next().unwrap_or(&stem);
+    after_dots.rsplit('_').next().unwrap_or(after_dots).to_lowercase()
 }
 
 fn get_dir(path: &str) -> String {
@@ -441,131 +758,937 @@ fn get_dir(path: &str) -> String {
         .unwrap_or_else(|| ".".to_string())
 }
 
-fn action_open(path: &str) {
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ActionErrorKind {
+    /// The selected file no longer exists.
+    MissingFile,
+    /// The program the action runs is not installed.
+    MissingProgram,
+    /// The action cannot run for this selection or platform.
+    Unavailable,
+    Failed,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+struct ActionError {
+    action: String,
+    kind: ActionErrorKind,
+    message: String,
+}
+
+impl ActionError {
+    fn new(action: &str, kind: ActionErrorKind, message: impl Into<String>) -> Self {
+        Self { action: action.to_string(), kind, message: message.into() }
+    }
+
+    /// Error from starting `program`; `NotFound` means the program itself is missing.
+    fn from_spawn(action: &str, program: &str, err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => Self::new(action, ActionErrorKind::MissingProgram, format!("{} not found", program)),
+            _ => Self::new(action, ActionErrorKind::Failed, format!("{}: {}", program, err)),
+        }
+    }
+
+    /// Error from a filesystem operation; `NotFound` means the file itself is missing.
+    fn from_file(action: &str, err: std::io::Error) -> Self {
+        let kind = match err.kind() {
+            std::io::ErrorKind::NotFound => ActionErrorKind::MissingFile,
+            _ => ActionErrorKind::Failed,
+        };
+        Self::new(action, kind, err.to_string())
+    }
+}
+
+impl std::fmt::Display for ActionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} failed: {}", self.action, self.message)
+    }
+}
+
+/// How long a started program is watched for a failing exit before it counts as launched.
+const EARLY_EXIT_WINDOW: Duration = Duration::from_millis(250);
+
+/// Reports `child` exiting with an error within [`EARLY_EXIT_WINDOW`], as launchers
+/// like `xdg-open` and `gtk-launch` do. A child still running afterwards is reaped on
+/// a thread so the daemon does not collect zombies.
+fn watch_child(action: &str, program: &str, mut child: std::process::Child) -> Result<(), ActionError> {
+    let started = Instant::now();
+    while started.elapsed() < EARLY_EXIT_WINDOW {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(ActionError::new(action, ActionErrorKind::Failed, format!("{} exited with {}", program, status))),
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(_) => break,
+        }
+    }
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+fn spawn_detached(action: &str, command: &mut Command) -> Result<(), ActionError> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let child = command.spawn().map_err(|e| ActionError::from_spawn(action, &program, e))?;
+    watch_child(action, &program, child)
+}
+
+fn get_log_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("qol-launcher.log")
+}
+
+const LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+fn format_log_line(timestamp: &str, path: &str, err: &ActionError) -> String {
+    let kind = serde_json::to_value(&err.kind).ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    format!("{} [{}] {} ({})\n", timestamp, kind, err, path)
+}
+
+/// Appends a failed action to the log, rotating it to `.old` once it grows past `LOG_MAX_BYTES`.
+fn log_action_error(path: &str, err: &ActionError) {
+    let log_path = get_log_path();
+    if fs::metadata(&log_path).map(|m| m.len() > LOG_MAX_BYTES).unwrap_or(false) {
+        let _ = fs::rename(&log_path, log_path.with_extension("log.old"));
+    }
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+    let line = format_log_line(&fileops::local_timestamp(secs), path, err);
+    if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(&log_path) {
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+fn action_open(path: &str) -> Result<(), ActionError> {
     #[cfg(target_os = "linux")]
     {
         if path.ends_with(".desktop") {
             if let Some(name) = std::path::Path::new(path).file_stem() {
-                let _ = Command::new("gtk-launch").arg(name).spawn();
-                return;
+                return spawn_detached("Open", Command::new("gtk-launch").arg(name));
             }
         }
-        let _ = Command::new("xdg-open").arg(path).spawn();
+        spawn_detached("Open", Command::new("xdg-open").arg(path))
     }
     #[cfg(target_os = "macos")]
-    let _ = Command::new("open").arg(path).spawn();
+    return spawn_detached("Open", Command::new("open").arg(path));
     #[cfg(target_os = "windows")]
-    let _ = Command::new("explorer").arg(path).spawn();
+    return spawn_detached("Open", Command::new("explorer").arg(path));
 }
 
-fn action_terminal(path: &str) {
-    let dir = get_dir(path);
-    #[cfg(target_os = "linux")]
-    for term in ["gnome-terminal", "konsole", "xfce4-terminal", "xterm"] {
-        let found = Command::new("which").arg(term).output().map(|o| o.status.success()).unwrap_or(false);
-        if !found { continue }
-        let _ = Command::new(term).arg("--working-directory").arg(&dir).spawn();
-        break;
+#[cfg(any(test, target_os = "linux"))]
+const TERMINAL_TEMPLATES: &[(&str, &str)] = &[
+    ("alacritty", "alacritty --working-directory {dir} -e {cmd}"),
+    ("kitty", "kitty --directory {dir} {cmd}"),
+    ("wezterm", "wezterm start --cwd {dir} -- {cmd}"),
+    ("foot", "foot --working-directory={dir} {cmd}"),
+    ("ghostty", "ghostty --working-directory={dir} -e {cmd}"),
+    ("gnome-terminal", "gnome-terminal --working-directory={dir} -- {cmd}"),
+    ("konsole", "konsole --workdir {dir} -e {cmd}"),
+    ("xfce4-terminal", "xfce4-terminal --working-directory={dir} -x {cmd}"),
+    ("xterm", "xterm -e {cmd}"),
+];
+
+#[cfg(any(test, target_os = "linux"))]
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|p| p.is_file())
+}
+
+#[cfg(any(test, target_os = "linux"))]
+fn builtin_terminal_template(program: &str) -> Option<String> {
+    let name = std::path::Path::new(program).file_name()?.to_str()?;
+    TERMINAL_TEMPLATES.iter()
+        .find(|(n, _)| *n == name)
+        .map(|(n, t)| format!("{}{}", program, &t[n.len()..]))
+}
+
+#[cfg(any(test, target_os = "linux"))]
+fn detect_terminal_template(
+    configured: &str,
+    terminal_env: Option<String>,
+    find: impl Fn(&str) -> Option<PathBuf>,
+) -> Option<String> {
+    if !configured.trim().is_empty() {
+        return Some(configured.to_string());
     }
-    #[cfg(target_os = "macos")]
-    let _ = Command::new("open").args(["-a", "Terminal", &dir]).spawn();
-    #[cfg(target_os = "windows")]
-    let _ = Command::new("cmd").args(["/c", "start", "cmd", "/k", &format!("cd /d {}", dir)]).spawn();
+    if let Some(term) = terminal_env.filter(|t| !t.trim().is_empty()) {
+        return Some(builtin_terminal_template(&term).unwrap_or_else(|| format!("{} -e {{cmd}}", term)));
+    }
+    if find("xdg-terminal-exec").is_some() {
+        return Some("xdg-terminal-exec {cmd}".to_string());
+    }
+    if let Some(path) = find("x-terminal-emulator") {
+        let target = fs::canonicalize(&path).unwrap_or(path);
+        return Some(builtin_terminal_template(&target.to_string_lossy())
+            .unwrap_or_else(|| "x-terminal-emulator -e {cmd}".to_string()));
+    }
+    TERMINAL_TEMPLATES.iter()
+        .find(|(name, _)| find(name).is_some())
+        .map(|(_, t)| t.to_string())
+}
+
+/// Splits a command line on whitespace, honouring single and double quotes.
+fn split_command_line(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+#[cfg(any(test, target_os = "linux"))]
+/// Splits the template before substituting, so paths never need quoting.
+/// A standalone `{cmd}` expands to one argument per command word.
+fn expand_terminal_template(template: &str, dir: &str, cmd: &[String]) -> Vec<String> {
+    split_command_line(template).into_iter().flat_map(|token| {
+        if token == "{cmd}" {
+            return cmd.to_vec();
+        }
+        vec![token.replace("{dir}", dir).replace("{cmd}", &cmd.join(" "))]
+    }).collect()
+}
+
+#[cfg(any(test, target_os = "linux"))]
+fn user_shell() -> String {
+    env::var("SHELL").unwrap_or_else(|_| "sh".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn spawn_terminal(dir: &str, cmd: &[String], config: &TerminalConfig) -> std::io::Result<std::process::Child> {
+    let template = detect_terminal_template(&config.command, env::var("TERMINAL").ok(), find_in_path)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no terminal emulator found"))?;
+    let argv = expand_terminal_template(&template, dir, cmd);
+    let Some((program, args)) = argv.split_first() else {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "empty terminal command"));
+    };
+    Command::new(program).args(args).current_dir(dir).spawn()
 }
 
-fn action_folder(path: &str) {
+fn action_terminal(path: &str) -> Result<(), ActionError> {
     let dir = get_dir(path);
     #[cfg(target_os = "linux")]
-    let _ = Command::new("xdg-open").arg(&dir).spawn();
+    return spawn_terminal(&dir, &[user_shell()], &config::current().terminal)
+        .map_err(|e| ActionError::from_spawn("Open in Terminal", "terminal emulator", e))
+        .and_then(|child| watch_child("Open in Terminal", "terminal emulator", child));
     #[cfg(target_os = "macos")]
-    let _ = Command::new("open").arg(&dir).spawn();
+    return spawn_detached("Open in Terminal", Command::new("open").args(["-a", "Terminal", &dir]));
     #[cfg(target_os = "windows")]
-    let _ = Command::new("explorer").arg(&dir).spawn();
+    return spawn_detached("Open in Terminal", Command::new("cmd").args(["/c", "start", "cmd", "/k", &format!("cd /d {}", dir)]));
 }
 
-fn action_copy(path: &str) {
+fn action_folder(path: &str) -> Result<(), ActionError> {
+    let dir = get_dir(path);
     #[cfg(target_os = "linux")]
-    let _ = Command::new("sh").args(["-c", &format!("echo -n '{}' | xclip -selection clipboard", path)]).spawn();
+    return spawn_detached("Open Containing Folder", Command::new("xdg-open").arg(&dir));
     #[cfg(target_os = "macos")]
-    let _ = Command::new("sh").args(["-c", &format!("echo -n '{}' | pbcopy", path)]).spawn();
+    return spawn_detached("Open Containing Folder", Command::new("open").arg(&dir));
     #[cfg(target_os = "windows")]
-    let _ = Command::new("powershell").args(["-Command", &format!("Set-Clipboard '{}'", path)]).spawn();
+    return spawn_detached("Open Containing Folder", Command::new("explorer").arg(&dir));
 }
 
-fn execute_action(path: &str, action: &str) {
-    let config = load_config();
-    record_access(path, &config);
-    match action {
-        "open" => action_open(path),
-        "terminal" => action_terminal(path),
-        "folder" => action_folder(path),
-        "copy" => action_copy(path),
-        _ => {}
+#[cfg(target_os = "linux")]
+fn is_wayland_session() -> bool {
+    env::var_os("WAYLAND_DISPLAY").is_some()
+        || env::var("XDG_SESSION_TYPE").map(|t| t == "wayland").unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn clipboard_candidates(wayland: bool) -> Vec<(&'static str, &'static [&'static str])> {
+    let wl_copy: (&str, &[&str]) = ("wl-copy", &[]);
+    let xclip: (&str, &[&str]) = ("xclip", &["-selection", "clipboard"]);
+    let xsel: (&str, &[&str]) = ("xsel", &["--clipboard", "--input"]);
+    if wayland {
+        vec![wl_copy, xclip, xsel]
+    } else {
+        vec![xclip, xsel, wl_copy]
     }
 }
 
-const HTML: &str = include_str!("../webview/index.html");
-const CSS: &str = include_str!("../webview/style.css");
-const JS: &str = include_str!("../webview/app.js");
+fn pipe_to_command(program: &str, args: &[&str], input: &[u8]) -> std::io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("{} exited with {}", program, status)));
+    }
+    Ok(())
+}
 
-fn build_html() -> String {
-    HTML.replace(r#"<link rel="stylesheet" href="style.css">"#, &format!("<style>{}</style>", CSS))
-        .replace(r#"<script src="app.js"></script>"#, &format!("<script>{}</script>", JS))
+fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut last_err = std::io::Error::new(std::io::ErrorKind::NotFound, "no clipboard tool found");
+        for (program, args) in clipboard_candidates(is_wayland_session()) {
+            match pipe_to_command(program, args, text.as_bytes()) {
+                Ok(()) => return Ok(()),
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err)
+    }
+    #[cfg(target_os = "macos")]
+    return pipe_to_command("pbcopy", &[], text.as_bytes());
+    #[cfg(target_os = "windows")]
+    return pipe_to_command(
+        "powershell",
+        &["-NoProfile", "-Command", "Set-Clipboard -Value ([Console]::In.ReadToEnd())"],
+        text.as_bytes(),
+    );
 }
 
-fn reset_ui(webview: &wry::WebView) {
-    let _ = webview.evaluate_script("document.getElementById('search').value = ''; window.onSearchResults([]);");
+#[cfg(any(test, target_os = "linux"))]
+const CLIPBOARD_TEXT: u32 = 0;
+#[cfg(any(test, target_os = "linux"))]
+const CLIPBOARD_URI_LIST: u32 = 1;
+#[cfg(any(test, target_os = "linux"))]
+const CLIPBOARD_GNOME_FILES: u32 = 2;
+
+fn percent_encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
 }
 
-fn create_window(event_loop: &tao::event_loop::EventLoop<UserEvent>) -> tao::window::Window {
-    WindowBuilder::new()
-        .with_title("Launcher")
-        .with_decorations(false)
-        .with_always_on_top(true)
-        .with_visible(false)
-        .with_inner_size(tao::dpi::LogicalSize::new(600.0, 400.0))
-        .with_resizable(false)
-        .build(event_loop)
-        .unwrap()
+#[cfg(any(test, target_os = "linux"))]
+fn file_uri(path: &str) -> String {
+    format!("file://{}", percent_encode_path(path))
 }
 
-fn create_ipc_handler(
-    proxy: tao::event_loop::EventLoopProxy<UserEvent>,
-    plugin_dir: PathBuf,
-    state: Arc<Mutex<AppState>>,
-) -> impl Fn(Request<String>) + 'static {
-    move |request: Request<String>| {
-        let Ok(msg) = serde_json::from_str::<IpcMessage>(request.body()) else { return };
-        match msg {
-            IpcMessage::Search { query } => {
-                let proxy = proxy.clone();
-                let dir = plugin_dir.clone();
-                std::thread::spawn(move || {
-                    let _ = proxy.send_event(UserEvent::SearchComplete(search(&query, &dir)));
-                });
-            }
-            IpcMessage::Execute { path, action } => {
-                execute_action(&path, &action);
-                state.lock().unwrap().should_exit = true;
-            }
-            IpcMessage::Close => {
-                state.lock().unwrap().should_exit = true;
-            }
-        }
+#[cfg(any(test, target_os = "linux"))]
+fn clipboard_payload(path: &str, info: u32) -> Vec<u8> {
+    match info {
+        CLIPBOARD_URI_LIST => format!("{}\r\n", file_uri(path)).into_bytes(),
+        CLIPBOARD_GNOME_FILES => format!("copy\n{}", file_uri(path)).into_bytes(),
+        _ => path.as_bytes().to_vec(),
     }
 }
 
+/// Takes ownership of the clipboard from the GTK main thread. `text` is offered to
+/// editors, `file` as a file reference so file managers paste the file itself.
 #[cfg(target_os = "linux")]
-fn show_window_linux(window: &tao::window::Window) {
-    use gtk::gdk::prelude::MonitorExt;
-    use gtk::prelude::{GtkWindowExt, WidgetExt};
-    use tao::platform::unix::WindowExtUnix;
-
-    let gtk_win = window.gtk_window();
-    let display = gtk_win.display();
-    let (focus_x, focus_y) = get_focused_window_position();
+fn own_clipboard(text: Option<&str>, file: Option<&str>) -> bool {
+    use gtk::{Clipboard, TargetEntry, TargetFlags};
 
-    let gdk_monitor = display.monitor_at_point(focus_x, focus_y)
+    let mut targets = Vec::new();
+    if text.is_some() {
+        for target in ["UTF8_STRING", "text/plain;charset=utf-8", "text/plain", "STRING"] {
+            targets.push(TargetEntry::new(target, TargetFlags::empty(), CLIPBOARD_TEXT));
+        }
+    }
+    if file.is_some() {
+        targets.push(TargetEntry::new("text/uri-list", TargetFlags::empty(), CLIPBOARD_URI_LIST));
+        targets.push(TargetEntry::new("x-special/gnome-copied-files", TargetFlags::empty(), CLIPBOARD_GNOME_FILES));
+    }
+    let (text, file) = (text.map(str::to_string), file.map(str::to_string));
+    Clipboard::get(&gtk::gdk::SELECTION_CLIPBOARD).set_with_data(&targets, move |_, data, info| {
+        match (info, &text, &file) {
+            (CLIPBOARD_TEXT, Some(text), _) => { data.set_text(text); }
+            (_, _, Some(file)) => data.set(&data.target(), 8, &clipboard_payload(file, info)),
+            _ => {}
+        }
+    })
+}
+
+fn action_copy(text: Option<&str>, file: Option<&str>) -> Result<(), ActionError> {
+    #[cfg(target_os = "linux")]
+    if own_clipboard(text, file) {
+        return Ok(());
+    }
+    let Some(fallback) = text.or(file) else { return Ok(()) };
+    copy_to_clipboard(fallback).map_err(|e| ActionError::from_spawn("Copy", "clipboard tool", e))
+}
+
+fn action_properties(path: &str) -> Result<(), ActionError> {
+    #[cfg(target_os = "linux")]
+    return spawn_detached("Properties", Command::new("gdbus")
+        .args([
+            "call", "--session",
+            "--dest", "org.freedesktop.FileManager1",
+            "--object-path", "/org/freedesktop/FileManager1",
+            "--method", "org.freedesktop.FileManager1.ShowItemProperties",
+        ])
+        .arg(format!("['{}']", file_uri(path)))
+        .arg("''")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null()));
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        Err(ActionError::new("Properties", ActionErrorKind::Unavailable, "not supported on this platform"))
+    }
+}
+
+/// Replaces each placeholder in `arg` in a single pass, so braces inside an inserted
+/// value are never expanded again.
+fn expand_placeholders(arg: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match values.iter().find(|(placeholder, _)| rest.starts_with(placeholder)) {
+            Some((placeholder, value)) => {
+                out.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn expand_action_template(template: &str, path: &str) -> Vec<String> {
+    let dir = get_dir(path);
+    let name = extract_filename(path);
+    let values = [("{path}", path), ("{dir}", dir.as_str()), ("{name}", name.as_str())];
+    split_command_line(template).into_iter()
+        .map(|arg| expand_placeholders(&arg, &values))
+        .collect()
+}
+
+fn matches_mime_pattern(pattern: &str, mime: &str) -> bool {
+    let (pattern, mime) = (pattern.to_ascii_lowercase(), mime.to_ascii_lowercase());
+    match pattern.strip_suffix("/*") {
+        Some(major) => mime.split('/').next() == Some(major),
+        None => pattern == mime,
+    }
+}
+
+fn action_applies(action: &CustomAction, path: &str, mime: impl FnOnce() -> String) -> bool {
+    if action.extensions.is_empty() && action.mime_types.is_empty() {
+        return true;
+    }
+    let ext = std::path::Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase());
+    let ext_match = ext.is_some_and(|ext| {
+        action.extensions.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+    });
+    if ext_match || action.mime_types.is_empty() {
+        return ext_match;
+    }
+    let mime = mime();
+    action.mime_types.iter().any(|p| matches_mime_pattern(p, &mime))
+}
+
+fn query_mime_type(path: &str) -> String {
+    if std::path::Path::new(path).is_dir() {
+        return "inode/directory".to_string();
+    }
+    Command::new("xdg-mime")
+        .args(["query", "filetype", path])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
+fn action_custom(path: &str, action: &CustomAction) -> Result<(), ActionError> {
+    let name = action.name.as_str();
+    if !action_applies(action, path, || query_mime_type(path)) {
+        return Err(ActionError::new(name, ActionErrorKind::Unavailable, "does not apply to this file"));
+    }
+    let argv = expand_action_template(&action.command, path);
+    let dir = get_dir(path);
+    if action.terminal {
+        #[cfg(target_os = "linux")]
+        return spawn_terminal(&dir, &argv, &config::current().terminal)
+            .map_err(|e| ActionError::from_spawn(name, "terminal emulator", e))
+            .and_then(|child| watch_child(name, "terminal emulator", child));
+        #[cfg(not(target_os = "linux"))]
+        return Err(ActionError::new(name, ActionErrorKind::Unavailable, "terminal actions are only supported on Linux"));
+    }
+    let Some((program, args)) = argv.split_first() else {
+        return Err(ActionError::new(name, ActionErrorKind::Failed, "command is empty"));
+    };
+    spawn_detached(name, Command::new(program).args(args).current_dir(&dir))
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+struct ActionInfo {
+    id: String,
+    label: String,
+    key: String,
+}
+
+const LINUX_ONLY_ACTIONS: &[&str] = &["open-with", "properties"];
+
+const BUILTIN_ACTIONS: &[(&str, &str, &str)] = &[
+    ("open", "Open", "Enter"),
+    ("open-with", "Open With…", ""),
+    ("terminal", "Open in Terminal", "Ctrl+Enter"),
+    ("folder", "Open Containing Folder", "Shift+Enter"),
+    ("copy", "Copy Path", "Alt+Enter"),
+    ("copy-file", "Copy File", ""),
+    ("copy-name", "Copy Name", ""),
+    ("rename", "Rename…", ""),
+    ("duplicate", "Duplicate", ""),
+    ("new-file", "New File…", ""),
+    ("new-folder", "New Folder…", ""),
+    ("trash", "Move to Trash", ""),
+    ("properties", "Properties", ""),
+];
+
+fn available_actions(path: &str, config: &Config, mime: impl Fn() -> String) -> Vec<ActionInfo> {
+    let mime_cache = std::cell::OnceCell::new();
+    let builtins = BUILTIN_ACTIONS.iter()
+        .filter(|(id, _, _)| cfg!(target_os = "linux") || !LINUX_ONLY_ACTIONS.contains(id))
+        .map(|(id, label, key)| ActionInfo { id: id.to_string(), label: label.to_string(), key: key.to_string() });
+    let custom = config.actions.iter()
+        .filter(|a| action_applies(a, path, || mime_cache.get_or_init(&mime).clone()))
+        .map(|a| ActionInfo { id: a.name.clone(), label: a.name.clone(), key: a.key.clone() });
+    builtins.chain(custom).collect()
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+struct AppInfo {
+    id: String,
+    name: String,
+    icon: Option<String>,
+    is_default: bool,
+}
+
+fn list_apps(path: &str) -> Vec<AppInfo> {
+    let mime = query_mime_type(path);
+    let entries = desktop::load_desktop_entries(&desktop::application_dirs());
+    let lists = desktop::load_mimeapps(&mime);
+    let default = desktop::default_app(&lists, &entries);
+    desktop::associated_apps(&lists, &entries, &mime).into_iter()
+        .filter_map(|id| entries.iter().find(|e| e.id == id))
+        .map(|e| AppInfo {
+            id: e.id.clone(),
+            name: e.name.clone(),
+            icon: e.icon.as_deref().and_then(resolve_icon_path).and_then(|p| icon_to_data_url(&p)),
+            is_default: default.as_ref() == Some(&e.id),
+        })
+        .collect()
+}
+
+fn action_open_with(path: &str, app: &str, set_default: bool) -> Result<(), ActionError> {
+    let config = config::current();
+    let entries = desktop::load_desktop_entries(&desktop::application_dirs());
+    let Some(entry) = entries.iter().find(|e| e.id == app) else {
+        return Err(ActionError::new("Open With", ActionErrorKind::MissingProgram, format!("{} is not installed", app)));
+    };
+    let child = desktop::launch(entry, &[path.to_string()], &config.terminal)
+        .map_err(|e| ActionError::from_spawn("Open With", &entry.name, e))?;
+    watch_child("Open With", &entry.name, child)?;
+    record_access(path, &config);
+    if set_default {
+        desktop::set_default_app(app, &query_mime_type(path))
+            .map_err(|e| ActionError::new("Set Default App", ActionErrorKind::Failed, e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn run_file_op(path: &str, op: &str, name: &str) -> Result<(), ActionError> {
+    let target = std::path::Path::new(path);
+    let dir = PathBuf::from(get_dir(path));
+    let (label, result) = match op {
+        "trash" => ("Move to Trash", fileops::trash(target)),
+        "rename" => ("Rename", fileops::rename(target, name)),
+        "duplicate" => ("Duplicate", fileops::duplicate(target)),
+        "new-file" => ("New File", fileops::create_file(&dir, name)),
+        "new-folder" => ("New Folder", fileops::create_folder(&dir, name)),
+        _ => return Err(ActionError::new(op, ActionErrorKind::Unavailable, "unknown file operation")),
+    };
+    result.map(|_| ()).map_err(|e| ActionError::from_file(label, e))
+}
+
+fn action_label(action: &str) -> &str {
+    BUILTIN_ACTIONS.iter()
+        .find(|(id, _, _)| *id == action)
+        .map_or(action, |(_, label, _)| label)
+}
+
+fn execute_action(path: &str, action: &str, config: &Config) -> Result<(), ActionError> {
+    if !std::path::Path::new(path).exists() {
+        return Err(ActionError::new(action_label(action), ActionErrorKind::MissingFile, format!("{} no longer exists", path)));
+    }
+    match action {
+        "open" => action_open(path),
+        "terminal" => action_terminal(path),
+        "folder" => action_folder(path),
+        "copy" => action_copy(Some(path), Some(path)),
+        "copy-file" => action_copy(None, Some(path)),
+        "copy-name" => action_copy(Some(&extract_filename(path)), None),
+        "properties" => action_properties(path),
+        name => match config.actions.iter().find(|a| a.name == name) {
+            Some(custom) => action_custom(path, custom),
+            None => Err(ActionError::new(name, ActionErrorKind::Unavailable, "no such action")),
+        },
+    }?;
+    record_access(path, config);
+    Ok(())
+}
+
+/// The results shown for `query` with the chosen one, use counts as they were before
+/// the pick; `None` when `chosen` was not among them.
+fn selection_for(query: &str, results: &[SearchResult], chosen: &str, freq: &FrequencyData, config: &Config) -> Option<history::Selection> {
+    let position = results.iter().position(|r| r.path == chosen)?;
+    let candidate = |r: &SearchResult| history::Candidate {
+        path: r.path.clone(),
+        name: r.name.clone(),
+        is_dir: r.is_dir,
+        uses: effective_uses(&r.path, freq, config),
+        ignored: gitignore::is_ignored(&r.path),
+    };
+    let mut candidates: Vec<_> = results.iter().take(history::MAX_CANDIDATES).map(candidate).collect();
+    if position >= history::MAX_CANDIDATES {
+        candidates.push(candidate(&results[position]));
+    }
+    Some(history::Selection {
+        query: query.to_string(),
+        chosen: chosen.to_string(),
+        candidates,
+        time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+    })
+}
+
+/// Actions that take ownership of the clipboard, which GTK only allows from the main
+/// thread. Every other action runs on a worker thread, as [`watch_child`] blocks.
+const CLIPBOARD_ACTIONS: [&str; 3] = ["copy", "copy-file", "copy-name"];
+
+/// Actions that count as picking a result for `launcher --tune`, along with Open With.
+/// Copying, renaming, trashing and the like say nothing about what the query wanted.
+const OPENING_ACTIONS: [&str; 2] = ["open", "folder"];
+
+/// Runs `action` and, when it succeeds on a result of the latest search, appends the
+/// pick to the selection history.
+fn record_selection(
+    state: &Mutex<AppState>,
+    path: &str,
+    action: impl FnOnce() -> Result<(), ActionError>,
+) -> Result<(), ActionError> {
+    let selection = state.lock().unwrap().last_search.as_ref()
+        .and_then(|(_, query, results)| selection_for(query, results, path, &load_frequency(), &config::current()));
+    action()?;
+    if let Some(selection) = selection {
+        let _ = history::append(&history::history_path(), selection);
+    }
+    Ok(())
+}
+
+fn finish_action(proxy: &tao::event_loop::EventLoopProxy<UserEvent>, path: &str, result: Result<(), ActionError>) {
+    if let Err(ref err) = result {
+        log_action_error(path, err);
+    }
+    let _ = proxy.send_event(UserEvent::ActionFinished(result));
+}
+
+const HTML: &str = include_str!("../webview/index.html");
+const CSS: &str = include_str!("../webview/style.css");
+const JS: &str = include_str!("../webview/app.js");
+
+fn build_html() -> String {
+    HTML.replace(r#"<link rel="stylesheet" href="style.css">"#, &format!("<style>{}</style>", CSS))
+        .replace(r#"<script src="app.js"></script>"#, &format!("<script>{}</script>", JS))
+}
+
+fn sync_config(webview: &wry::WebView) {
+    let config = config::current();
+    let Ok(actions) = serde_json::to_string(&config.actions) else { return };
+    let Ok(issues) = serde_json::to_string(&config::issues()) else { return };
+    let Ok(profiles) = serde_json::to_string(&config.profiles.keys().collect::<Vec<_>>()) else { return };
+    let _ = webview.evaluate_script(&format!("window.setCustomActions({})", actions));
+    let _ = webview.evaluate_script(&format!("window.setConfigIssues({})", issues));
+    let _ = webview.evaluate_script(&format!("window.setProfiles({})", profiles));
+}
+
+fn reset_ui(webview: &wry::WebView) {
+    let _ = webview.evaluate_script("window.resetLauncher();");
+}
+
+fn show_launcher(
+    window: &tao::window::Window,
+    webview: &wry::WebView,
+    visibility: &mut Visibility,
+    options: &protocol::ShowOptions,
+) {
+    visibility.shown();
+    #[cfg(target_os = "linux")]
+    show_window_linux(window);
+    #[cfg(not(target_os = "linux"))]
+    show_window_other(window);
+    sync_config(webview);
+    let _ = webview.evaluate_script("document.getElementById('search').focus();");
+    if options.query.is_some() || options.mode.is_some() {
+        let query = serde_json::to_string(options.query.as_deref().unwrap_or("")).unwrap_or_default();
+        let mode = serde_json::to_string(&options.mode.unwrap_or_default()).unwrap_or_default();
+        let _ = webview.evaluate_script(&format!("window.setQuery({}, {})", query, mode));
+    }
+}
+
+fn hide_launcher(window: &tao::window::Window, webview: &wry::WebView, visibility: &mut Visibility) {
+    visibility.hidden(Instant::now());
+    window.set_visible(false);
+    reset_ui(webview);
+}
+
+fn handle_window_command(
+    command: &protocol::Command,
+    window: &tao::window::Window,
+    webview: &wry::WebView,
+    visibility: &mut Visibility,
+) -> Result<serde_json::Value, String> {
+    match command {
+        protocol::Command::Show(options) => show_launcher(window, webview, visibility, options),
+        protocol::Command::Hide => hide_launcher(window, webview, visibility),
+        protocol::Command::Toggle => match visibility.toggle(Instant::now()) {
+            ToggleAction::Show => show_launcher(window, webview, visibility, &protocol::ShowOptions::default()),
+            ToggleAction::Hide => hide_launcher(window, webview, visibility),
+            ToggleAction::Stay => {}
+        },
+        protocol::Command::Execute { path, action } => {
+            execute_action(path, action, &config::current()).map_err(|e| {
+                log_action_error(path, &e);
+                e.to_string()
+            })?;
+        }
+        protocol::Command::ReloadConfig => {
+            let issues = config::reload();
+            sync_config(webview);
+            return serde_json::to_value(issues).map_err(|e| e.to_string());
+        }
+        protocol::Command::Status => {
+            return Ok(serde_json::json!({
+                "visible": visibility.visible,
+                "pid": std::process::id(),
+                "version": env!("CARGO_PKG_VERSION"),
+            }));
+        }
+        protocol::Command::Query { .. } | protocol::Command::Version | protocol::Command::Kill => {
+            return Err("not a window command".to_string());
+        }
+    }
+    Ok(serde_json::Value::Null)
+}
+
+fn create_window(event_loop: &tao::event_loop::EventLoop<UserEvent>) -> tao::window::Window {
+    WindowBuilder::new()
+        .with_title("Launcher")
+        .with_decorations(false)
+        .with_always_on_top(true)
+        .with_visible(false)
+        .with_inner_size(tao::dpi::LogicalSize::new(600.0, 400.0))
+        .with_resizable(false)
+        .build(event_loop)
+        .unwrap()
+}
+
+fn create_ipc_handler(
+    proxy: tao::event_loop::EventLoopProxy<UserEvent>,
+    plugin_dir: PathBuf,
+    state: Arc<Mutex<AppState>>,
+) -> impl Fn(Request<String>) + 'static {
+    move |request: Request<String>| {
+        let Ok(msg) = serde_json::from_str::<IpcMessage>(request.body()) else { return };
+        match msg {
+            IpcMessage::Search { query, mode } => {
+                let proxy = proxy.clone();
+                let dir = plugin_dir.clone();
+                let state = state.clone();
+                let number = state.lock().unwrap().start_search();
+                std::thread::spawn(move || {
+                    let results = search(&query, &dir, mode);
+                    let note = search_note(&query, &results);
+                    if state.lock().unwrap().finish_search(number, query, results.clone()) {
+                        let _ = proxy.send_event(UserEvent::SearchComplete(results, note));
+                    }
+                });
+            }
+            IpcMessage::ListActions { path } => {
+                let proxy = proxy.clone();
+                std::thread::spawn(move || {
+                    let actions = available_actions(&path, &config::current(), || query_mime_type(&path));
+                    let _ = proxy.send_event(UserEvent::ActionsListed(actions));
+                });
+            }
+            IpcMessage::ListApps { path } => {
+                let proxy = proxy.clone();
+                std::thread::spawn(move || {
+                    let _ = proxy.send_event(UserEvent::AppsListed(list_apps(&path)));
+                });
+            }
+            IpcMessage::FileOp { path, op, name } => {
+                let proxy = proxy.clone();
+                std::thread::spawn(move || {
+                    finish_action(&proxy, &path, run_file_op(&path, &op, &name));
+                });
+            }
+            IpcMessage::OpenWith { path, app, set_default } => {
+                let proxy = proxy.clone();
+                let state = state.clone();
+                std::thread::spawn(move || {
+                    let result = record_selection(&state, &path, || action_open_with(&path, &app, set_default));
+                    finish_action(&proxy, &path, result);
+                });
+            }
+            IpcMessage::Execute { path, action } if CLIPBOARD_ACTIONS.contains(&action.as_str()) => {
+                finish_action(&proxy, &path, execute_action(&path, &action, &config::current()));
+            }
+            IpcMessage::Execute { path, action } => {
+                let proxy = proxy.clone();
+                let state = state.clone();
+                std::thread::spawn(move || {
+                    let result = if OPENING_ACTIONS.contains(&action.as_str()) {
+                        record_selection(&state, &path, || execute_action(&path, &action, &config::current()))
+                    } else {
+                        execute_action(&path, &action, &config::current())
+                    };
+                    finish_action(&proxy, &path, result);
+                });
+            }
+            IpcMessage::Close => {
+                state.lock().unwrap().should_exit = true;
+            }
+            IpcMessage::DmenuSelect { .. } => {}
+        }
+    }
+}
+
+fn create_dmenu_ipc_handler(
+    proxy: tao::event_loop::EventLoopProxy<UserEvent>,
+    input: Arc<dmenu::Input>,
+    options: dmenu::DmenuOptions,
+) -> impl Fn(Request<String>) + 'static {
+    let searches = Arc::new(AtomicU64::new(0));
+    move |request: Request<String>| {
+        let Ok(msg) = serde_json::from_str::<IpcMessage>(request.body()) else { return };
+        match msg {
+            IpcMessage::Search { query, .. } => {
+                let number = searches.fetch_add(1, Ordering::SeqCst) + 1;
+                let (proxy, input, searches) = (proxy.clone(), input.clone(), searches.clone());
+                std::thread::spawn(move || {
+                    let results = dmenu::filter(&input, &query, &config::current());
+                    // A newer keystroke's results are on their way; these would replace them.
+                    if searches.load(Ordering::SeqCst) == number {
+                        let _ = proxy.send_event(UserEvent::SearchComplete(results, None));
+                    }
+                });
+            }
+            IpcMessage::DmenuSelect { lines: selected, modifier } => {
+                print!("{}", dmenu::selection_output(&input.lines, &selected));
+                let _ = std::io::stdout().flush();
+                std::process::exit(options.exit_code(&modifier));
+            }
+            IpcMessage::Close => std::process::exit(dmenu::EXIT_CANCELLED),
+            _ => {}
+        }
+    }
+}
+
+fn build_webview(
+    window: &tao::window::Window,
+    handler: impl Fn(Request<String>) + 'static,
+    init_script: &str,
+) -> wry::WebView {
+    let html = build_html();
+    let builder = WebViewBuilder::new()
+        .with_html(&html)
+        .with_initialization_script(init_script)
+        .with_ipc_handler(handler);
+
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios", target_os = "android"))]
+    let webview = builder.build(window).unwrap();
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios", target_os = "android")))]
+    let webview = {
+        use tao::platform::unix::WindowExtUnix;
+        use wry::WebViewBuilderExtUnix;
+        let wv = builder.build_gtk(window.default_vbox().unwrap()).unwrap();
+        window.set_visible(false);
+        wv
+    };
+
+    webview
+}
+
+/// Runs a standalone picker over stdin lines; never returns.
+fn run_dmenu(options: dmenu::DmenuOptions) -> ! {
+    let lines = dmenu::read_lines(std::io::stdin().lock());
+    let input = Arc::new(dmenu::Input::new(lines, config::current().transliterate));
+    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
+    let proxy = event_loop.create_proxy();
+    let window = create_window(&event_loop);
+    let handler = create_dmenu_ipc_handler(proxy.clone(), input, options.clone());
+    let webview = build_webview(&window, handler, &dmenu::init_script(&options));
+    let _ = proxy.send_event(UserEvent::Show(protocol::ShowOptions { query: Some(String::new()), mode: None }));
+
+    let mut visibility = Visibility::default();
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        match event {
+            Event::UserEvent(UserEvent::Show(ref options)) => show_launcher(&window, &webview, &mut visibility, options),
+            Event::UserEvent(UserEvent::SearchComplete(ref results, note)) => {
+                let Ok(json) = serde_json::to_string(results) else { return };
+                let note = serde_json::to_string(&note).unwrap_or_default();
+                let _ = webview.evaluate_script(&format!("window.onSearchResults({}, {})", json, note));
+            }
+            Event::WindowEvent { event: WindowEvent::CloseRequested, .. } |
+            Event::WindowEvent { event: WindowEvent::Focused(false), .. } => {
+                std::process::exit(dmenu::EXIT_CANCELLED);
+            }
+            _ => {}
+        }
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn show_window_linux(window: &tao::window::Window) {
+    use gtk::gdk::prelude::MonitorExt;
+    use gtk::prelude::{GtkWindowExt, WidgetExt};
+    use tao::platform::unix::WindowExtUnix;
+
+    let gtk_win = window.gtk_window();
+    let display = gtk_win.display();
+    let (focus_x, focus_y) = get_focused_window_position();
+
+    let gdk_monitor = display.monitor_at_point(focus_x, focus_y)
         .or_else(|| display.monitor_at_point(0, 0))
         .unwrap();
     let geom = gdk_monitor.geometry();
@@ -582,205 +1705,1123 @@ fn show_window_linux(window: &tao::window::Window) {
     gtk_win.set_keep_above(true);
     window.set_visible(true);
 
-    let timestamp = (std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() & 0xFFFFFFFF) as u32;
-    gtk_win.present_with_time(timestamp);
+    gtk_win.present_with_time(x11_server_time(gtk_win));
     gtk_win.grab_focus();
 }
 
-#[cfg(not(target_os = "linux"))]
-fn show_window_other(window: &tao::window::Window) {
-    if let Some(monitor) = window.current_monitor() {
-        let mon_pos = monitor.position();
-        let mon_size = monitor.size();
-        let win_size = window.outer_size();
-        let (x, y) = calculate_centered_position(
-            mon_size.width, mon_size.height,
-            win_size.width, win_size.height,
-            mon_pos.x, mon_pos.y,
-        );
-        window.set_outer_position(tao::dpi::PhysicalPosition::new(x, y));
-    }
-    window.set_visible(true);
-    window.set_focus();
-}
+/// X server time from a property-change round trip, which focus-stealing prevention
+/// accepts. Outside X11 this is `GDK_CURRENT_TIME` (0).
+#[cfg(target_os = "linux")]
+fn x11_server_time(gtk_win: &gtk::ApplicationWindow) -> u32 {
+    use gtk::prelude::{Cast, WidgetExt};
+
+    gtk_win.window()
+        .and_then(|w| w.downcast::<gdkx11::X11Window>().ok())
+        .map(|w| gdkx11::functions::x11_get_server_time(&w))
+        .unwrap_or(0)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn show_window_other(window: &tao::window::Window) {
+    if let Some(monitor) = window.current_monitor() {
+        let mon_pos = monitor.position();
+        let mon_size = monitor.size();
+        let win_size = window.outer_size();
+        let (x, y) = calculate_centered_position(
+            mon_size.width, mon_size.height,
+            win_size.width, win_size.height,
+            mon_pos.x, mon_pos.y,
+        );
+        window.set_outer_position(tao::dpi::PhysicalPosition::new(x, y));
+    }
+    window.set_visible(true);
+    window.set_focus();
+}
+
+#[derive(Debug, PartialEq)]
+struct QueryOptions {
+    query: String,
+    json: bool,
+    limit: Option<usize>,
+    mode: protocol::SearchMode,
+    explain: bool,
+}
+
+/// Value following `flag`; an error when the flag is the last argument.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a str>, String> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) => args.get(i + 1).map(|v| Some(v.as_str())).ok_or_else(|| format!("{} needs a value", flag)),
+        None => Ok(None),
+    }
+}
+
+fn parse_mode_arg(args: &[String]) -> Result<Option<protocol::SearchMode>, String> {
+    flag_value(args, "--mode")?.map(str::parse).transpose()
+}
+
+/// Parses `--query <text>` and `--mode <mode>` for the window opened by `--show`.
+fn parse_show_args(args: &[String]) -> Result<protocol::ShowOptions, String> {
+    Ok(protocol::ShowOptions {
+        query: flag_value(args, "--query")?.map(str::to_string),
+        mode: parse_mode_arg(args)?,
+    })
+}
+
+/// Parses `--query <text> [--json] [--limit N] [--mode M] [--explain]`; `Ok(None)` when `--query` is
+/// absent or `--show` asks for the window instead.
+fn parse_query_args(args: &[String]) -> Result<Option<QueryOptions>, String> {
+    if args.iter().any(|a| a == "--show") {
+        return Ok(None);
+    }
+    let Some(query) = flag_value(args, "--query")? else { return Ok(None) };
+    let limit = flag_value(args, "--limit")?
+        .map(|value| value.parse().map_err(|_| format!("invalid --limit: {}", value)))
+        .transpose()?;
+    Ok(Some(QueryOptions {
+        query: query.to_string(),
+        json: args.iter().any(|a| a == "--json"),
+        limit,
+        mode: parse_mode_arg(args)?.unwrap_or_default(),
+        explain: args.iter().any(|a| a == "--explain"),
+    }))
+}
+
+/// Applies a query's `limit` and drops the score breakdowns unless `explain` asks for them.
+fn trim_query_results(mut results: Vec<SearchResult>, limit: Option<usize>, explain: bool) -> Vec<SearchResult> {
+    if let Some(limit) = limit {
+        results.truncate(limit);
+    }
+    if !explain {
+        for r in &mut results {
+            r.score = None;
+        }
+    }
+    results
+}
+
+/// One path per line, followed by a tab and its score breakdown when there is one.
+fn format_query_results(results: &[SearchResult], json: bool) -> String {
+    if json {
+        return serde_json::to_string_pretty(results).unwrap_or_default() + "\n";
+    }
+    results.iter()
+        .map(|r| match &r.score {
+            Some(score) => format!("{}\t{}\n", r.path, score),
+            None => format!("{}\n", r.path),
+        })
+        .collect()
+}
+
+/// Asks the running daemon for results, falling back to searching in-process when none is running.
+fn query_results(options: &QueryOptions) -> Result<Vec<SearchResult>, String> {
+    let command = protocol::Command::Query {
+        query: options.query.clone(),
+        limit: options.limit,
+        mode: options.mode,
+        explain: options.explain,
+    };
+    match protocol::send(&protocol::socket_path(), command, QUERY_TIMEOUT) {
+        Ok(response) if response.ok => serde_json::from_value(response.result).map_err(|e| e.to_string()),
+        Ok(response) => Err(response.error.unwrap_or_else(|| "query failed".to_string())),
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused) => {
+            let results = search(&options.query, &get_plugin_dir(), options.mode);
+            Ok(trim_query_results(results, options.limit, options.explain))
+        }
+        Err(e) => Err(format!("daemon did not answer: {}", e)),
+    }
+}
+
+fn run_query(options: &QueryOptions) -> i32 {
+    match query_results(options) {
+        Ok(results) => {
+            if let Some(note) = search_note(&options.query, &results) {
+                eprintln!("launcher: {}", note);
+            }
+            print!("{}", format_query_results(&results, options.json));
+            0
+        }
+        Err(e) => {
+            eprintln!("launcher: {}", e);
+            1
+        }
+    }
+}
+
+/// `launcher --tune [--profile <name>]`: fits weights to the selection history and
+/// saves them as a profile, `tuned` by default.
+fn run_tune(args: &[String]) -> i32 {
+    let name = match flag_value(args, "--profile") {
+        Ok(name) => name.unwrap_or("tuned"),
+        Err(e) => {
+            eprintln!("launcher: {}", e);
+            return 2;
+        }
+    };
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        eprintln!("launcher: profile names cannot be empty or contain spaces");
+        return 2;
+    }
+    let history = history::load(&history::history_path());
+    if history.len() < tune::MIN_SELECTIONS {
+        eprintln!("launcher: {} selections recorded, tuning needs at least {}", history.len(), tune::MIN_SELECTIONS);
+        return 1;
+    }
+    let tuned = tune::fit(&history, &config::current());
+    let path = config::config_path();
+    if let Err(e) = config::save_profile(&path, name, &config::Profile::from_config(&tuned.config)) {
+        eprintln!("launcher: cannot write {}: {}", path.display(), e);
+        return 1;
+    }
+    println!("{} selections, mean reciprocal rank {:.3} -> {:.3}", history.len(), tuned.before, tuned.after);
+    println!("Saved profile \"{}\" to {}; start a query with @{} to use it", name, path.display(), name);
+    0
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--print-config-schema") {
+        println!("{}", serde_json::to_string_pretty(&config::schema()).unwrap_or_default());
+        return;
+    }
+    if args.iter().any(|a| a == "--tune") {
+        std::process::exit(run_tune(&args));
+    }
+    match parse_query_args(&args) {
+        Ok(Some(options)) => std::process::exit(run_query(&options)),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("launcher: {}", e);
+            std::process::exit(2);
+        }
+    }
+
+    match dmenu::parse_args(&args) {
+        Ok(Some(options)) => run_dmenu(options),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("launcher: {}", e);
+            std::process::exit(2);
+        }
+    }
+
+    let show = match parse_show_args(&args) {
+        Ok(show) => show,
+        Err(e) => {
+            eprintln!("launcher: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if env::args().any(|a| a == "--kill") {
+        let _ = send_socket_command(protocol::Command::Kill);
+        return;
+    }
+
+    let toggle = env::args().any(|a| a == "--toggle");
+    let request = if toggle { protocol::Command::Toggle } else { protocol::Command::Show(show.clone()) };
+
+    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
+    let proxy = event_loop.create_proxy();
+
+    if !start_socket_listener(proxy.clone(), get_plugin_dir(), &request) {
+        return;
+    }
+
+    let state = Arc::new(Mutex::new(AppState::default()));
+    let window = create_window(&event_loop);
+    let handler = create_ipc_handler(proxy.clone(), get_plugin_dir(), state.clone());
+    let webview = build_webview(&window, handler, "");
+
+    if toggle || env::args().any(|a| a == "--show") {
+        let _ = proxy.send_event(UserEvent::Show(show));
+    }
+
+    let watcher = proxy.clone();
+    config::watch(move || {
+        let _ = watcher.send_event(UserEvent::ConfigChanged);
+    });
+    // Load the application index before the first search needs it.
+    std::thread::spawn(apps::entries);
+
+    let mut visibility = Visibility::default();
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+
+        if std::mem::take(&mut state.lock().unwrap().should_exit) {
+            hide_launcher(&window, &webview, &mut visibility);
+        }
+
+        match event {
+            Event::UserEvent(UserEvent::Show(ref options)) => show_launcher(&window, &webview, &mut visibility, options),
+            Event::UserEvent(UserEvent::Socket(ref command, ref reply)) => {
+                let _ = reply.send(handle_window_command(command, &window, &webview, &mut visibility));
+            }
+            Event::UserEvent(UserEvent::ConfigChanged) => sync_config(&webview),
+            Event::UserEvent(UserEvent::SearchComplete(ref results, note)) => {
+                let Ok(json) = serde_json::to_string(results) else { return };
+                let note = serde_json::to_string(&note).unwrap_or_default();
+                let _ = webview.evaluate_script(&format!("window.onSearchResults({}, {})", json, note));
+            }
+            Event::UserEvent(UserEvent::ActionsListed(ref actions)) => {
+                let Ok(json) = serde_json::to_string(actions) else { return };
+                let _ = webview.evaluate_script(&format!("window.onActions({})", json));
+            }
+            Event::UserEvent(UserEvent::AppsListed(ref apps)) => {
+                let Ok(json) = serde_json::to_string(apps) else { return };
+                let _ = webview.evaluate_script(&format!("window.onApps({})", json));
+            }
+            Event::UserEvent(UserEvent::ActionFinished(Ok(()))) => hide_launcher(&window, &webview, &mut visibility),
+            Event::UserEvent(UserEvent::ActionFinished(Err(ref err))) => {
+                let Ok(json) = serde_json::to_string(err) else { return };
+                let _ = webview.evaluate_script(&format!("window.onActionError({})", json));
+            }
+            Event::WindowEvent { event: WindowEvent::CloseRequested, .. } |
+            Event::WindowEvent { event: WindowEvent::Focused(false), .. } => {
+                hide_launcher(&window, &webview, &mut visibility);
+            }
+            _ => {}
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod parse_search_result {
+        use super::*;
+
+        #[test]
+        fn extracts_filename_from_absolute_path() {
+            let result = parse_search_result("/a/b/c/file.txt");
+            assert_eq!(result.name, "file.txt");
+            assert_eq!(result.path, "/a/b/c/file.txt");
+        }
+
+        #[test]
+        fn handles_path_with_spaces() {
+            let result = parse_search_result("/a/b c/d e.txt");
+            assert_eq!(result.name, "d e.txt");
+        }
+
+        #[test]
+        fn handles_root_path() {
+            let result = parse_search_result("/");
+            assert_eq!(result.name, "/");
+            assert_eq!(result.path, "/");
+        }
+
+        #[test]
+        fn handles_hidden_file() {
+            let result = parse_search_result("/a/b/.hidden");
+            assert_eq!(result.name, ".hidden");
+        }
+
+        #[test]
+        fn handles_deeply_nested_path() {
+            let result = parse_search_result("/a/b/c/d/e/f/g/h/i/file.rs");
+            assert_eq!(result.name, "file.rs");
+            assert_eq!(result.path, "/a/b/c/d/e/f/g/h/i/file.rs");
+        }
+
+        #[test]
+        fn handles_unicode_filename() {
+            let result = parse_search_result("/a/b/文档/файл.txt");
+            assert_eq!(result.name, "файл.txt");
+        }
+    }
+
+    mod get_dir {
+        use super::*;
+        use std::fs;
+        use tempfile::tempdir;
+
+        #[test]
+        fn returns_path_if_directory_exists() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let path = dir.path().to_str().unwrap();
+
+            // Act
+            let result = get_dir(path);
+
+            // Assert
+            assert_eq!(result, path);
+        }
+
+        #[test]
+        fn returns_parent_for_file_path() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let file_path = dir.path().join("test.txt");
+            fs::write(&file_path, "test").unwrap();
+
+            // Act
+            let result = get_dir(file_path.to_str().unwrap());
+
+            // Assert
+            assert_eq!(result, dir.path().to_str().unwrap());
+        }
+
+        #[test]
+        fn returns_parent_for_nonexistent_file() {
+            // Arrange
+            let path = "/some/nonexistent/path/file.txt";
+
+            // Act
+            let result = get_dir(path);
+
+            // Assert
+            assert_eq!(result, "/some/nonexistent/path");
+        }
+
+        #[test]
+        fn returns_dot_for_root_file() {
+            // Arrange
+            let path = "file.txt";
+
+            // Act
+            let result = get_dir(path);
+
+            // Assert
+            assert_eq!(result, ".");
+        }
+    }
+
+    mod clipboard {
+        use super::*;
+        use tempfile::tempdir;
+
+        const HOSTILE_NAMES: &[&str] = &[
+            "it's a file.txt",
+            "'; touch CANARY; '",
+            "$(touch CANARY)",
+            "`touch CANARY`",
+            "a\"b\\c $HOME %PATH%.txt",
+        ];
+
+        #[test]
+        #[cfg(target_os = "linux")]
+        fn prefers_wl_copy_on_wayland() {
+            // Act
+            let candidates = clipboard_candidates(true);
+
+            // Assert
+            assert_eq!(candidates[0].0, "wl-copy");
+            assert_eq!(candidates.len(), 3);
+        }
+
+        #[test]
+        #[cfg(target_os = "linux")]
+        fn prefers_xclip_on_x11() {
+            // Act
+            let candidates = clipboard_candidates(false);
+
+            // Assert
+            assert_eq!(candidates[0].0, "xclip");
+            assert_eq!(candidates[1].0, "xsel");
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn pipes_hostile_filenames_verbatim() {
+            for name in HOSTILE_NAMES {
+                // Arrange
+                let dir = tempdir().unwrap();
+                let out = dir.path().join("out");
+                let canary = dir.path().join("CANARY");
+                let input = name.replace("CANARY", canary.to_str().unwrap());
+
+                // Act
+                pipe_to_command("tee", &[out.to_str().unwrap()], input.as_bytes()).unwrap();
+
+                // Assert
+                assert_eq!(fs::read_to_string(&out).unwrap(), input);
+                assert!(!canary.exists(), "input was interpreted by a shell: {}", name);
+            }
+        }
+
+        #[test]
+        fn file_uri_percent_encodes_reserved_bytes() {
+            // Act
+            let uri = file_uri("/a/b c/it's #1.txt");
+
+            // Assert
+            assert_eq!(uri, "file:///a/b%20c/it%27s%20%231.txt");
+        }
+
+        #[test]
+        fn file_uri_encodes_unicode_as_utf8() {
+            // Act
+            let uri = file_uri("/tmp/Café");
+
+            // Assert
+            assert_eq!(uri, "file:///tmp/Caf%C3%A9");
+        }
+
+        #[test]
+        fn payload_for_text_is_plain_path() {
+            // Act
+            let payload = clipboard_payload("/a/b.txt", CLIPBOARD_TEXT);
+
+            // Assert
+            assert_eq!(payload, b"/a/b.txt");
+        }
+
+        #[test]
+        fn payload_for_uri_list_is_crlf_terminated() {
+            // Act
+            let payload = clipboard_payload("/a/b.txt", CLIPBOARD_URI_LIST);
+
+            // Assert
+            assert_eq!(payload, b"file:///a/b.txt\r\n");
+        }
+
+        #[test]
+        fn payload_for_gnome_files_has_copy_verb() {
+            // Act
+            let payload = clipboard_payload("/a/b.txt", CLIPBOARD_GNOME_FILES);
+
+            // Assert
+            assert_eq!(payload, b"copy\nfile:///a/b.txt");
+        }
+
+        #[test]
+        fn reports_missing_program() {
+            // Act
+            let result = pipe_to_command("qol-launcher-no-such-tool", &[], b"x");
+
+            // Assert
+            assert!(result.is_err());
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn reports_failed_exit_status() {
+            // Act
+            let result = pipe_to_command("false", &[], b"x");
+
+            // Assert
+            assert!(result.is_err());
+        }
+    }
+
+    mod terminal {
+        use super::*;
+
+        fn found(names: &'static [&'static str]) -> impl Fn(&str) -> Option<PathBuf> {
+            move |p| names.contains(&p).then(|| PathBuf::from(format!("/usr/bin/{}", p)))
+        }
+
+        fn cmd(words: &[&str]) -> Vec<String> {
+            words.iter().map(|w| w.to_string()).collect()
+        }
+
+        #[test]
+        fn configured_command_wins() {
+            // Act
+            let template = detect_terminal_template("foot {cmd}", Some("kitty".into()), found(&["xterm"]));
+
+            // Assert
+            assert_eq!(template.as_deref(), Some("foot {cmd}"));
+        }
+
+        #[test]
+        fn terminal_env_uses_builtin_template() {
+            // Act
+            let template = detect_terminal_template("", Some("kitty".into()), found(&[]));
+
+            // Assert
+            assert_eq!(template.as_deref(), Some("kitty --directory {dir} {cmd}"));
+        }
+
+        #[test]
+        fn terminal_env_keeps_absolute_path() {
+            // Act
+            let template = detect_terminal_template("", Some("/opt/bin/alacritty".into()), found(&[]));
+
+            // Assert
+            assert_eq!(template.as_deref(), Some("/opt/bin/alacritty --working-directory {dir} -e {cmd}"));
+        }
+
+        #[test]
+        fn unknown_terminal_env_falls_back_to_dash_e() {
+            // Act
+            let template = detect_terminal_template("", Some("st".into()), found(&[]));
+
+            // Assert
+            assert_eq!(template.as_deref(), Some("st -e {cmd}"));
+        }
+
+        #[test]
+        fn prefers_xdg_terminal_exec_over_builtins() {
+            // Act
+            let template = detect_terminal_template("", None, found(&["xdg-terminal-exec", "xterm"]));
+
+            // Assert
+            assert_eq!(template.as_deref(), Some("xdg-terminal-exec {cmd}"));
+        }
+
+        #[test]
+        fn falls_back_to_first_installed_builtin() {
+            // Act
+            let template = detect_terminal_template("", None, found(&["xterm", "konsole"]));
+
+            // Assert
+            assert_eq!(template.as_deref(), Some("konsole --workdir {dir} -e {cmd}"));
+        }
+
+        #[test]
+        fn returns_none_when_nothing_installed() {
+            assert!(detect_terminal_template("", None, found(&[])).is_none());
+        }
+
+        #[test]
+        fn expands_dir_without_splitting_spaces() {
+            // Act
+            let argv = expand_terminal_template("foot --working-directory={dir} {cmd}", "/a b/c", &cmd(&["bash"]));
+
+            // Assert
+            assert_eq!(argv, ["foot", "--working-directory=/a b/c", "bash"]);
+        }
+
+        #[test]
+        fn expands_standalone_cmd_into_separate_arguments() {
+            // Act
+            let argv = expand_terminal_template("alacritty -e {cmd}", "/tmp", &cmd(&["sh", "-c", "git log; exec bash"]));
+
+            // Assert
+            assert_eq!(argv, ["alacritty", "-e", "sh", "-c", "git log; exec bash"]);
+        }
+
+        #[test]
+        fn does_not_interpret_quotes_in_dir() {
+            // Act
+            let argv = expand_terminal_template("kitty --directory {dir} {cmd}", "/it's/$(x)", &cmd(&["bash"]));
+
+            // Assert
+            assert_eq!(argv[2], "/it's/$(x)");
+        }
+
+        #[test]
+        fn config_without_terminal_section_still_parses() {
+            // Arrange
+            let json = r#"{"half_life_days": 7.0, "frequency_bonus": 500, "prefer_apps": true,
+                "penalize_hidden": true, "depth_penalty": 2, "exact_bonus": 0,
+                "prefix_penalty": 100, "contains_penalty": 200}"#;
+
+            // Act
+            let config: Config = serde_json::from_str(json).unwrap();
+
+            // Assert
+            assert!(config.terminal.command.is_empty());
+        }
+    }
+
+    mod custom_actions {
+        use super::*;
+
+        fn action(extensions: &[&str], mime_types: &[&str]) -> CustomAction {
+            CustomAction {
+                name: "test".to_string(),
+                command: "true".to_string(),
+                key: String::new(),
+                extensions: extensions.iter().map(|e| e.to_string()).collect(),
+                mime_types: mime_types.iter().map(|m| m.to_string()).collect(),
+                terminal: false,
+            }
+        }
+
+        #[test]
+        fn split_command_line_honours_quotes() {
+            // Act
+            let args = split_command_line(r#"sh -c 'wc -l "$1"' _ "a b""#);
+
+            // Assert
+            assert_eq!(args, ["sh", "-c", r#"wc -l "$1""#, "_", "a b"]);
+        }
+
+        #[test]
+        fn split_command_line_keeps_empty_quoted_argument() {
+            // Act
+            let args = split_command_line("a '' b");
+
+            // Assert
+            assert_eq!(args, ["a", "", "b"]);
+        }
+
+        #[test]
+        fn expands_placeholders_per_argument() {
+            // Act
+            let argv = expand_action_template("code --goto {path} --folder {dir} {name}", "/a b/it's.txt");
+
+            // Assert
+            assert_eq!(argv, ["code", "--goto", "/a b/it's.txt", "--folder", "/a b", "it's.txt"]);
+        }
+
+        #[test]
+        fn hostile_path_stays_one_argument() {
+            // Act
+            let argv = expand_action_template("scp {path} host:/uploads/", "/tmp/'; rm -rf ~; '");
+
+            // Assert
+            assert_eq!(argv.len(), 3);
+            assert_eq!(argv[1], "/tmp/'; rm -rf ~; '");
+        }
+
+        #[test]
+        fn braces_in_the_path_are_not_expanded_again() {
+            // Act
+            let argv = expand_action_template("cp {path} {dir}/{name}.bak", "/tmp/{dir}/{name}.txt");
+
+            // Assert
+            assert_eq!(argv, ["cp", "/tmp/{dir}/{name}.txt", "/tmp/{dir}/{name}.txt.bak"]);
+        }
+
+        #[test]
+        fn unknown_placeholder_is_kept() {
+            // Act
+            let argv = expand_action_template("echo {size} {path}", "/a/b.txt");
+
+            // Assert
+            assert_eq!(argv, ["echo", "{size}", "/a/b.txt"]);
+        }
+
+        #[test]
+        fn action_without_filters_always_applies() {
+            // Arrange
+            let a = action(&[], &[]);
+
+            // Act
+            let applies = action_applies(&a, "/a/b", || unreachable!());
+
+            // Assert
+            assert!(applies);
+        }
+
+        #[test]
+        fn extension_filter_is_case_insensitive_and_dot_agnostic() {
+            // Arrange
+            let a = action(&[".pdf", "PNG"], &[]);
+
+            // Act
+            let applies = ["/a/doc.PDF", "/a/img.png", "/a/notes.txt"].map(|path| action_applies(&a, path, || unreachable!()));
+
+            // Assert
+            assert_eq!(applies, [true, true, false]);
+        }
+
+        #[test]
+        fn mime_filter_supports_wildcards() {
+            // Arrange
+            let a = action(&[], &["image/*"]);
+
+            // Act
+            let applies = ["image/jpeg", "text/plain"].map(|mime| action_applies(&a, "/a/photo", || mime.to_string()));
+
+            // Assert
+            assert_eq!(applies, [true, false]);
+        }
+
+        #[test]
+        fn mime_wildcard_ignores_case() {
+            // Arrange
+            let a = action(&[], &["Image/*"]);
+
+            // Act
+            let applies = action_applies(&a, "/a/photo", || "IMAGE/JPEG".to_string());
+
+            // Assert
+            assert!(applies);
+        }
+
+        #[test]
+        fn mime_filter_matches_directories() {
+            // Arrange
+            let a = action(&[], &["inode/directory"]);
+
+            // Act
+            let applies = action_applies(&a, "/a/dir", || "inode/directory".to_string());
+
+            // Assert
+            assert!(applies);
+        }
+
+        #[test]
+        fn deserializes_with_optional_fields_missing() {
+            // Arrange
+            let json = r#"{"name": "Open in VS Code", "command": "code {path}"}"#;
+
+            // Act
+            let parsed: CustomAction = serde_json::from_str(json).unwrap();
+
+            // Assert
+            assert_eq!(parsed.name, "Open in VS Code");
+            assert!(parsed.key.is_empty());
+            assert!(!parsed.terminal);
+        }
+    }
+
+    mod available_actions {
+        use super::*;
+
+        fn custom(name: &str, extensions: &[&str]) -> CustomAction {
+            CustomAction {
+                name: name.to_string(),
+                command: "true".to_string(),
+                key: String::new(),
+                extensions: extensions.iter().map(|e| e.to_string()).collect(),
+                mime_types: Vec::new(),
+                terminal: false,
+            }
+        }
+
+        fn ids(actions: &[ActionInfo]) -> Vec<&str> {
+            actions.iter().map(|a| a.id.as_str()).collect()
+        }
+
+        #[test]
+        fn lists_builtins_first_with_bindings() {
+            // Act
+            let actions = available_actions("/a/b.txt", &Config::default(), || unreachable!());
+
+            // Assert
+            let bound: Vec<_> = actions.iter().filter(|a| !a.key.is_empty()).map(|a| a.id.as_str()).collect();
+            assert_eq!(actions[0].id, "open");
+            assert_eq!(bound, ["open", "terminal", "folder", "copy"]);
+            assert!(ids(&actions).contains(&"copy-name"));
+        }
+
+        #[test]
+        fn appends_applicable_custom_actions() {
+            // Arrange
+            let config = Config {
+                actions: vec![custom("Print", &["pdf"]), custom("Edit", &["txt"])],
+                ..Config::default()
+            };
+
+            // Act
+            let actions = available_actions("/a/b.txt", &config, || unreachable!());
+
+            // Assert
+            assert!(ids(&actions).contains(&"Edit"));
+            assert!(!ids(&actions).contains(&"Print"));
+        }
 
-fn main() {
-    if env::args().any(|a| a == "--kill") {
-        send_socket_command(b"kill");
-        return;
-    }
+        #[test]
+        fn queries_mime_type_at_most_once() {
+            // Arrange
+            let mut a = custom("A", &[]);
+            a.mime_types = vec!["text/*".to_string()];
+            let mut b = a.clone();
+            b.name = "B".to_string();
+            let config = Config { actions: vec![a, b], ..Config::default() };
+            let calls = std::cell::Cell::new(0);
 
-    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
-    let proxy = event_loop.create_proxy();
+            // Act
+            let actions = available_actions("/a/b", &config, || {
+                calls.set(calls.get() + 1);
+                "text/plain".to_string()
+            });
 
-    if !start_socket_listener(proxy.clone()) {
-        return;
+            // Assert
+            assert_eq!(calls.get(), 1);
+            assert!(ids(&actions).contains(&"A") && ids(&actions).contains(&"B"));
+        }
     }
 
-    let state = Arc::new(Mutex::new(AppState::default()));
-    let window = create_window(&event_loop);
-    let handler = create_ipc_handler(proxy.clone(), get_plugin_dir(), state.clone());
+    mod parse_query_args {
+        use super::*;
 
-    let html = build_html();
+        fn args(list: &[&str]) -> Vec<String> {
+            std::iter::once("launcher").chain(list.iter().copied()).map(String::from).collect()
+        }
 
-    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios", target_os = "android"))]
-    let webview = WebViewBuilder::new()
-        .with_html(&html)
-        .with_ipc_handler(handler)
-        .build(&window)
-        .unwrap();
+        #[test]
+        fn none_without_query_flag() {
+            assert_eq!(parse_query_args(&args(&["--show"])), Ok(None));
+        }
 
-    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios", target_os = "android")))]
-    let webview = {
-        use tao::platform::unix::WindowExtUnix;
-        use wry::WebViewBuilderExtUnix;
-        let wv = WebViewBuilder::new()
-            .with_html(&html)
-            .with_ipc_handler(handler)
-            .build_gtk(window.default_vbox().unwrap())
-            .unwrap();
-        window.set_visible(false);
-        wv
-    };
+        #[test]
+        fn parses_all_options() {
+            // Act
+            let options = parse_query_args(&args(&["--query", "foo bar", "--json", "--limit", "5"])).unwrap();
 
-    if env::args().any(|a| a == "--show") {
-        let _ = proxy.send_event(UserEvent::Show);
-    }
+            // Assert
+            let expected = QueryOptions {
+                query: "foo bar".to_string(),
+                json: true,
+                limit: Some(5),
+                mode: protocol::SearchMode::All,
+                explain: false,
+            };
+            assert_eq!(options, Some(expected));
+        }
 
-    event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Wait;
+        #[test]
+        fn reads_explain_flag() {
+            let options = parse_query_args(&args(&["--query", "notes", "--explain"])).unwrap().unwrap();
+            assert!(options.explain);
+        }
 
-        if std::mem::take(&mut state.lock().unwrap().should_exit) {
-            window.set_visible(false);
-            reset_ui(&webview);
+        #[test]
+        fn reads_mode() {
+            let options = parse_query_args(&args(&["--query", "fire", "--mode", "apps"])).unwrap().unwrap();
+            assert_eq!(options.mode, protocol::SearchMode::Apps);
         }
 
-        match event {
-            Event::UserEvent(UserEvent::Show) => {
-                #[cfg(target_os = "linux")]
-                show_window_linux(&window);
-                #[cfg(not(target_os = "linux"))]
-                show_window_other(&window);
-                let _ = webview.evaluate_script("document.getElementById('search').focus();");
-            }
-            Event::UserEvent(UserEvent::SearchComplete(ref results)) => {
-                let Ok(json) = serde_json::to_string(results) else { return };
-                let _ = webview.evaluate_script(&format!("window.onSearchResults({})", json));
-            }
-            Event::WindowEvent { event: WindowEvent::CloseRequested, .. } |
-            Event::WindowEvent { event: WindowEvent::Focused(false), .. } => {
-                window.set_visible(false);
-                reset_ui(&webview);
-            }
-            _ => {}
+        #[test]
+        fn show_flag_opens_window_instead() {
+            assert_eq!(parse_query_args(&args(&["--show", "--query", "~/Projects/"])), Ok(None));
         }
-    });
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        #[test]
+        fn rejects_missing_term() {
+            assert!(parse_query_args(&args(&["--query"])).is_err());
+        }
 
-    mod parse_search_result {
+        #[test]
+        fn rejects_invalid_limit() {
+            assert_eq!(
+                parse_query_args(&args(&["--query", "a", "--limit", "many"])),
+                Err("invalid --limit: many".to_string())
+            );
+        }
+    }
+
+    mod parse_show_args {
         use super::*;
 
+        fn args(list: &[&str]) -> Vec<String> {
+            std::iter::once("launcher").chain(list.iter().copied()).map(String::from).collect()
+        }
+
         #[test]
-        fn extracts_filename_from_absolute_path() {
-            let result = parse_search_result("/a/b/c/file.txt");
-            assert_eq!(result.name, "file.txt");
-            assert_eq!(result.path, "/a/b/c/file.txt");
+        fn empty_without_flags() {
+            assert_eq!(parse_show_args(&args(&["--show"])), Ok(protocol::ShowOptions::default()));
         }
 
         #[test]
-        fn handles_path_with_spaces() {
-            let result = parse_search_result("/a/b c/d e.txt");
-            assert_eq!(result.name, "d e.txt");
+        fn reads_query_and_mode() {
+            // Act
+            let options = parse_show_args(&args(&["--show", "--query", "~/Projects/", "--mode", "files"])).unwrap();
+
+            // Assert
+            assert_eq!(options.query.as_deref(), Some("~/Projects/"));
+            assert_eq!(options.mode, Some(protocol::SearchMode::Files));
         }
 
         #[test]
-        fn handles_root_path() {
-            let result = parse_search_result("/");
-            assert_eq!(result.name, "/");
-            assert_eq!(result.path, "/");
+        fn rejects_unknown_mode() {
+            assert!(parse_show_args(&args(&["--show", "--mode", "music"])).is_err());
+        }
+    }
+
+    mod format_query_results {
+        use super::*;
+
+        fn result(path: &str) -> SearchResult {
+            SearchResult { path: path.to_string(), name: extract_filename(path), is_dir: false, icon: None, score: None }
         }
 
         #[test]
-        fn handles_hidden_file() {
-            let result = parse_search_result("/a/b/.hidden");
-            assert_eq!(result.name, ".hidden");
+        fn prints_one_path_per_line() {
+            let output = format_query_results(&[result("/a/x.txt"), result("/b/y.txt")], false);
+            assert_eq!(output, "/a/x.txt\n/b/y.txt\n");
         }
 
         #[test]
-        fn handles_deeply_nested_path() {
-            let result = parse_search_result("/a/b/c/d/e/f/g/h/i/file.rs");
-            assert_eq!(result.name, "file.rs");
-            assert_eq!(result.path, "/a/b/c/d/e/f/g/h/i/file.rs");
+        fn json_round_trips() {
+            // Arrange
+            let results = vec![result("/a/x.txt")];
+
+            // Act
+            let output = format_query_results(&results, true);
+
+            // Assert
+            let parsed: Vec<SearchResult> = serde_json::from_str(&output).unwrap();
+            assert_eq!(parsed, results);
         }
 
         #[test]
-        fn handles_unicode_filename() {
-            let result = parse_search_result("/a/b/文档/файл.txt");
-            assert_eq!(result.name, "файл.txt");
+        fn empty_plain_output_is_empty() {
+            assert_eq!(format_query_results(&[], false), "");
+        }
+
+        #[test]
+        fn appends_score_breakdown_when_present() {
+            // Arrange
+            let score = ScoreBreakdown {
+                match_penalty: 100,
+                type_penalty: 0,
+                path_penalty: 54,
+                length_penalty: 5,
+                frequency_bonus: 20,
+                total: 139,
+            };
+            let explained = SearchResult { score: Some(score), ..result("/a/x.txt") };
+
+            // Act
+            let output = format_query_results(&[explained], false);
+
+            // Assert
+            assert_eq!(output, "/a/x.txt\t139 = match 100 + type 0 + path 54 + length 5 - frequency 20\n");
         }
     }
 
-    mod get_dir {
+    mod trim_query_results {
         use super::*;
-        use std::fs;
-        use tempfile::tempdir;
+
+        fn scored(path: &str) -> SearchResult {
+            SearchResult { score: Some(ScoreBreakdown::default()), ..parse_search_result(path) }
+        }
 
         #[test]
-        fn returns_path_if_directory_exists() {
+        fn drops_scores_unless_explaining() {
             // Arrange
-            let dir = tempdir().unwrap();
-            let path = dir.path().to_str().unwrap();
+            let results = vec![scored("/a"), scored("/b"), scored("/c")];
 
             // Act
-            let result = get_dir(path);
+            let plain = trim_query_results(results.clone(), Some(2), false);
+            let explained = trim_query_results(results, None, true);
 
             // Assert
-            assert_eq!(result, path);
+            assert_eq!(plain.len(), 2);
+            assert!(plain.iter().all(|r| r.score.is_none()));
+            assert!(explained.iter().all(|r| r.score.is_some()));
         }
+    }
+
+    mod action_error {
+        use super::*;
 
         #[test]
-        fn returns_parent_for_file_path() {
+        fn missing_program_is_reported_by_name() {
             // Arrange
-            let dir = tempdir().unwrap();
-            let file_path = dir.path().join("test.txt");
-            fs::write(&file_path, "test").unwrap();
+            let err = std::io::Error::new(std::io::ErrorKind::NotFound, "No such file or directory");
 
             // Act
-            let result = get_dir(file_path.to_str().unwrap());
+            let result = ActionError::from_spawn("Open", "gtk-launch", err);
 
             // Assert
-            assert_eq!(result, dir.path().to_str().unwrap());
+            assert_eq!(result.kind, ActionErrorKind::MissingProgram);
+            assert_eq!(result.to_string(), "Open failed: gtk-launch not found");
         }
 
         #[test]
-        fn returns_parent_for_nonexistent_file() {
+        fn other_spawn_errors_are_failures() {
+            let err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+            let result = ActionError::from_spawn("Open", "xdg-open", err);
+            assert_eq!(result.kind, ActionErrorKind::Failed);
+            assert_eq!(result.message, "xdg-open: denied");
+        }
+
+        #[test]
+        fn serializes_kind_in_snake_case() {
+            let err = ActionError::new("Open", ActionErrorKind::MissingFile, "gone");
+            let json = serde_json::to_value(&err).unwrap();
+            assert_eq!(json["kind"], "missing_file");
+            assert_eq!(json["action"], "Open");
+        }
+
+        #[test]
+        fn log_line_includes_kind_and_path() {
             // Arrange
-            let path = "/some/nonexistent/path/file.txt";
+            let err = ActionError::new("Open", ActionErrorKind::MissingProgram, "xdg-open not found");
 
             // Act
-            let result = get_dir(path);
+            let line = format_log_line("2026-01-01T00:00:00", "/a/b.txt", &err);
 
             // Assert
-            assert_eq!(result, "/some/nonexistent/path");
+            assert_eq!(line, "2026-01-01T00:00:00 [missing_program] Open failed: xdg-open not found (/a/b.txt)\n");
         }
+    }
+
+    mod spawn_detached {
+        use super::*;
 
         #[test]
-        fn returns_dot_for_root_file() {
+        fn reports_early_failing_exit() {
+            // Act
+            let result = spawn_detached("Open", Command::new("sh").args(["-c", "exit 3"]));
+
+            // Assert
+            let err = result.unwrap_err();
+            assert_eq!(err.kind, ActionErrorKind::Failed);
+            assert!(err.message.starts_with("sh exited with"), "{}", err.message);
+        }
+
+        #[test]
+        fn quick_success_is_ok() {
+            assert_eq!(spawn_detached("Open", &mut Command::new("true")), Ok(()));
+        }
+
+        #[test]
+        fn long_running_child_counts_as_launched() {
+            // Act
+            let started = Instant::now();
+            let result = spawn_detached("Open", Command::new("sleep").arg("5"));
+
+            // Assert
+            assert_eq!(result, Ok(()));
+            assert!(started.elapsed() < Duration::from_secs(2));
+        }
+    }
+
+    mod execute_action {
+        use super::*;
+
+        #[test]
+        fn fails_when_file_is_gone() {
+            // Act
+            let result = execute_action("/nonexistent/qol-launcher/file.txt", "folder", &Config::default());
+
+            // Assert
+            let err = result.unwrap_err();
+            assert_eq!(err.kind, ActionErrorKind::MissingFile);
+            assert_eq!(err.action, "Open Containing Folder");
+        }
+
+        #[test]
+        fn unknown_action_is_unavailable() {
+            let result = execute_action("/", "no-such-action", &Config::default());
+            assert_eq!(result.unwrap_err().kind, ActionErrorKind::Unavailable);
+        }
+    }
+
+    mod run_file_op {
+        use super::*;
+        use tempfile::tempdir;
+
+        #[test]
+        fn reports_failure_with_operation_label() {
+            // Act
+            let result = run_file_op("/nonexistent/qol-launcher/a", "rename", "b");
+
+            // Assert
+            let err = result.unwrap_err();
+            assert_eq!(err.kind, ActionErrorKind::MissingFile);
+            assert!(err.to_string().starts_with("Rename failed: "));
+        }
+
+        #[test]
+        fn creates_in_result_directory() {
             // Arrange
-            let path = "file.txt";
+            let dir = tempdir().unwrap();
+            let file = dir.path().join("a.txt");
+            fs::write(&file, "").unwrap();
 
             // Act
-            let result = get_dir(path);
+            run_file_op(file.to_str().unwrap(), "new-folder", "sub").unwrap();
 
             // Assert
-            assert_eq!(result, ".");
+            assert!(dir.path().join("sub").is_dir());
+        }
+
+        #[test]
+        fn rejects_unknown_operation() {
+            assert!(run_file_op("/a", "shred", "").is_err());
         }
     }
 
@@ -831,39 +2872,104 @@ mod tests {
 
             // Assert
             match msg {
-                IpcMessage::Search { query } => assert_eq!(query, "test query"),
+                IpcMessage::Search { query, .. } => assert_eq!(query, "test query"),
                 _ => panic!("Expected Search variant"),
             }
         }
 
         #[test]
-        fn deserializes_execute_message() {
+        fn deserializes_execute_message() {
+            // Arrange
+            let json = r#"{"type": "execute", "path": "/a/b/file.txt", "action": "open"}"#;
+
+            // Act
+            let msg: IpcMessage = serde_json::from_str(json).unwrap();
+
+            // Assert
+            match msg {
+                IpcMessage::Execute { path, action } => {
+                    assert_eq!(path, "/a/b/file.txt");
+                    assert_eq!(action, "open");
+                }
+                _ => panic!("Expected Execute variant"),
+            }
+        }
+
+        #[test]
+        fn deserializes_close_message() {
+            // Arrange
+            let json = r#"{"type": "close"}"#;
+
+            // Act
+            let msg: IpcMessage = serde_json::from_str(json).unwrap();
+
+            // Assert
+            assert!(matches!(msg, IpcMessage::Close));
+        }
+
+        #[test]
+        fn deserializes_list_actions_message() {
+            // Arrange
+            let json = r#"{"type": "list_actions", "path": "/a/b/file.txt"}"#;
+
+            // Act
+            let msg: IpcMessage = serde_json::from_str(json).unwrap();
+
+            // Assert
+            match msg {
+                IpcMessage::ListActions { path } => assert_eq!(path, "/a/b/file.txt"),
+                _ => panic!("Expected ListActions variant"),
+            }
+        }
+
+        #[test]
+        fn deserializes_open_with_message() {
+            // Arrange
+            let json = r#"{"type": "open_with", "path": "/a/b.txt", "app": "gedit.desktop", "set_default": true}"#;
+
+            // Act
+            let msg: IpcMessage = serde_json::from_str(json).unwrap();
+
+            // Assert
+            match msg {
+                IpcMessage::OpenWith { path, app, set_default } => {
+                    assert_eq!(path, "/a/b.txt");
+                    assert_eq!(app, "gedit.desktop");
+                    assert!(set_default);
+                }
+                _ => panic!("Expected OpenWith variant"),
+            }
+        }
+
+        #[test]
+        fn open_with_defaults_to_not_setting_default() {
             // Arrange
-            let json = r#"{"type": "execute", "path": "/a/b/file.txt", "action": "open"}"#;
+            let json = r#"{"type": "open_with", "path": "/a", "app": "b.desktop"}"#;
 
             // Act
             let msg: IpcMessage = serde_json::from_str(json).unwrap();
 
             // Assert
-            match msg {
-                IpcMessage::Execute { path, action } => {
-                    assert_eq!(path, "/a/b/file.txt");
-                    assert_eq!(action, "open");
-                }
-                _ => panic!("Expected Execute variant"),
-            }
+            assert!(matches!(msg, IpcMessage::OpenWith { set_default: false, .. }));
         }
 
         #[test]
-        fn deserializes_close_message() {
+        fn deserializes_file_op_message() {
             // Arrange
-            let json = r#"{"type": "close"}"#;
+            let json = r#"{"type": "file_op", "path": "/a/b.txt", "op": "rename", "name": "c.txt"}"#;
 
             // Act
             let msg: IpcMessage = serde_json::from_str(json).unwrap();
 
             // Assert
-            assert!(matches!(msg, IpcMessage::Close));
+            match msg {
+                IpcMessage::FileOp { path, op, name } => {
+                    assert_eq!(path, "/a/b.txt");
+                    assert_eq!(op, "rename");
+                    assert_eq!(name, "c.txt");
+                }
+                _ => panic!("Expected FileOp variant"),
+            }
         }
 
         #[test]
@@ -900,7 +3006,7 @@ mod tests {
 
             // Assert
             match msg {
-                IpcMessage::Search { query } => assert_eq!(query, ""),
+                IpcMessage::Search { query, .. } => assert_eq!(query, ""),
                 _ => panic!("Expected Search variant"),
             }
         }
@@ -915,7 +3021,7 @@ mod tests {
 
             // Assert
             match msg {
-                IpcMessage::Search { query } => {
+                IpcMessage::Search { query, .. } => {
                     assert_eq!(query, r#"test "quoted" path/with\backslash"#)
                 }
                 _ => panic!("Expected Search variant"),
@@ -951,6 +3057,7 @@ mod tests {
                 name: "file.txt".to_string(),
                 is_dir: false,
                 icon: None,
+                score: None,
             };
             let json = serde_json::to_string(&result).unwrap();
             assert!(json.contains(r#""path":"/a/b/file.txt""#));
@@ -965,6 +3072,7 @@ mod tests {
                 name: "docs".to_string(),
                 is_dir: true,
                 icon: None,
+                score: None,
             };
             let json = serde_json::to_string(&result).unwrap();
             assert!(json.contains(r#""is_dir":true"#));
@@ -973,8 +3081,8 @@ mod tests {
         #[test]
         fn serializes_vec_of_results() {
             let results = vec![
-                SearchResult { path: "/a".to_string(), name: "a".to_string(), is_dir: true, icon: None },
-                SearchResult { path: "/b".to_string(), name: "b".to_string(), is_dir: false, icon: None },
+                SearchResult { path: "/a".to_string(), name: "a".to_string(), is_dir: true, icon: None, score: None },
+                SearchResult { path: "/b".to_string(), name: "b".to_string(), is_dir: false, icon: None, score: None },
             ];
             let json = serde_json::to_string(&results).unwrap();
             assert!(json.starts_with('['));
@@ -998,14 +3106,119 @@ mod tests {
 
         #[test]
         fn can_set_should_exit() {
+            // Act
+            let state = AppState { should_exit: true, ..AppState::default() };
+
+            // Assert
+            assert!(state.should_exit);
+        }
+
+        #[test]
+        fn late_older_search_does_not_replace_newer() {
             // Arrange
             let mut state = AppState::default();
+            let (older, newer) = (state.start_search(), state.start_search());
+            state.finish_search(newer, "notes".to_string(), Vec::new());
 
             // Act
-            state.should_exit = true;
+            let kept = state.finish_search(older, "no".to_string(), Vec::new());
 
             // Assert
-            assert!(state.should_exit);
+            assert!(!kept);
+            assert_eq!(state.last_search.map(|(_, query, _)| query).as_deref(), Some("notes"));
+        }
+
+        #[test]
+        fn searches_finishing_in_order_are_kept() {
+            // Arrange
+            let mut state = AppState::default();
+            let (older, newer) = (state.start_search(), state.start_search());
+
+            // Act
+            let kept = [
+                state.finish_search(older, "no".to_string(), Vec::new()),
+                state.finish_search(newer, "notes".to_string(), Vec::new()),
+            ];
+
+            // Assert
+            assert_eq!(kept, [true, true]);
+            assert_eq!(state.last_search.map(|(number, _, _)| number), Some(newer));
+        }
+    }
+
+    mod visibility {
+        use super::*;
+
+        #[test]
+        fn first_toggle_shows() {
+            let visibility = Visibility::default();
+            assert_eq!(visibility.toggle(Instant::now()), ToggleAction::Show);
+        }
+
+        #[test]
+        fn toggle_hides_visible_window() {
+            // Arrange
+            let mut visibility = Visibility::default();
+            visibility.shown();
+
+            // Act
+            let action = visibility.toggle(Instant::now());
+
+            // Assert
+            assert_eq!(action, ToggleAction::Hide);
+        }
+
+        #[test]
+        fn toggle_right_after_focus_loss_keeps_window_hidden() {
+            // Arrange
+            let now = Instant::now();
+            let mut visibility = Visibility::default();
+            visibility.shown();
+            visibility.hidden(now);
+
+            // Act
+            let action = visibility.toggle(now + Duration::from_millis(50));
+
+            // Assert
+            assert_eq!(action, ToggleAction::Stay);
+        }
+
+        #[test]
+        fn toggle_after_grace_period_shows_again() {
+            // Arrange
+            let now = Instant::now();
+            let mut visibility = Visibility::default();
+            visibility.shown();
+            visibility.hidden(now);
+
+            // Act
+            let action = visibility.toggle(now + TOGGLE_GRACE);
+
+            // Assert
+            assert_eq!(action, ToggleAction::Show);
+        }
+
+        #[test]
+        fn hiding_hidden_window_keeps_original_time() {
+            // Arrange
+            let now = Instant::now();
+            let mut visibility = Visibility::default();
+            visibility.shown();
+            visibility.hidden(now);
+
+            // Act
+            visibility.hidden(now + Duration::from_secs(10));
+
+            // Assert
+            assert_eq!(visibility.hidden_at, Some(now));
+            assert!(!visibility.visible);
+        }
+
+        #[test]
+        fn hide_without_show_does_not_start_grace() {
+            let mut visibility = Visibility::default();
+            visibility.hidden(Instant::now());
+            assert_eq!(visibility.hidden_at, None);
         }
     }
 
@@ -1047,7 +3260,7 @@ mod tests {
 
             // Assert
             assert_eq!(x, 1920 + (1920 - 600) / 2);
-            assert_eq!(y, 0 + (1080 - 400) / 3);
+            assert_eq!(y, (1080 - 400) / 3);
         }
 
         #[test]
@@ -1095,6 +3308,51 @@ mod tests {
         }
     }
 
+    mod wants_apps {
+        use super::*;
+
+        #[test]
+        fn needs_words_or_app_filter() {
+            // Arrange
+            let all = protocol::SearchMode::All;
+
+            // Assert
+            assert!(wants_apps(&query::parse("fire"), all));
+            assert!(wants_apps(&query::parse("type:app"), all));
+            assert!(!wants_apps(&query::parse("modified:<7d"), all));
+            assert!(!wants_apps(&query::parse("!draft"), all));
+            assert!(!wants_apps(&query::parse("fire"), protocol::SearchMode::Files));
+        }
+    }
+
+    mod app_result {
+        use super::*;
+
+        #[test]
+        fn uses_entry_name_and_path() {
+            // Arrange
+            let entry = desktop::DesktopEntry {
+                path: PathBuf::from("/usr/share/applications/org.gnome.Nautilus.desktop"),
+                name: "Files".to_string(),
+                ..Default::default()
+            };
+
+            // Act
+            let result = app_result(&entry);
+
+            // Assert
+            assert_eq!(result.path, "/usr/share/applications/org.gnome.Nautilus.desktop");
+            assert_eq!(result.name, "Files");
+            assert!(!result.is_dir);
+        }
+
+        #[test]
+        fn falls_back_to_file_name() {
+            let entry = desktop::DesktopEntry { path: PathBuf::from("/a/tool.desktop"), ..Default::default() };
+            assert_eq!(app_result(&entry).name, "tool.desktop");
+        }
+    }
+
     mod extract_app_id {
         use super::*;
 
@@ -1157,7 +3415,7 @@ mod tests {
         use super::*;
 
         fn make_result(path: &str, name: &str) -> SearchResult {
-            SearchResult { path: path.to_string(), name: name.to_string(), is_dir: false, icon: None }
+            SearchResult { path: path.to_string(), name: name.to_string(), is_dir: false, icon: None, score: None }
         }
 
         #[test]
@@ -1244,34 +3502,65 @@ mod tests {
 
         #[test]
         fn standard_app_dir_has_low_penalty() {
-            let score = score_path_quality("/usr/share/applications/foo.desktop", &cfg());
+            let score = score_path_quality("/usr/share/applications/foo.desktop", false, &cfg());
             assert!(score < 50);
         }
 
         #[test]
         fn autostart_dir_has_higher_penalty() {
-            let score = score_path_quality("/etc/xdg/autostart/foo.desktop", &cfg());
+            let score = score_path_quality("/etc/xdg/autostart/foo.desktop", false, &cfg());
             assert!(score >= 80);
         }
 
         #[test]
         fn hidden_dirs_heavily_penalized() {
-            let score = score_path_quality("/a/.config/autostart/foo.desktop", &cfg());
+            let score = score_path_quality("/a/.config/autostart/foo.desktop", false, &cfg());
             assert!(score >= 500);
         }
 
         #[test]
         fn deep_paths_penalized() {
-            let shallow_score = score_path_quality("/usr/share/applications/foo.desktop", &cfg());
-            let deep_score = score_path_quality("/a/b/c/d/e/f/g/h/foo.desktop", &cfg());
+            let shallow_score = score_path_quality("/usr/share/applications/foo.desktop", false, &cfg());
+            let deep_score = score_path_quality("/a/b/c/d/e/f/g/h/foo.desktop", false, &cfg());
             assert!(deep_score > shallow_score);
         }
 
         #[test]
         fn user_local_apps_have_low_penalty() {
-            let score = score_path_quality("/a/.local/share/applications/foo.desktop", &cfg());
+            let score = score_path_quality("/a/.local/share/applications/foo.desktop", false, &cfg());
             assert!(score < 100);
         }
+
+        #[test]
+        fn gitignored_paths_penalized() {
+            // Arrange
+            let dir = tempfile::tempdir().unwrap();
+            std::fs::create_dir(dir.path().join(".git")).unwrap();
+            std::fs::write(dir.path().join(".gitignore"), "dist/\n").unwrap();
+            let ignored = dir.path().join("dist/app.js").to_string_lossy().to_string();
+            let tracked = dir.path().join("src/app.js").to_string_lossy().to_string();
+
+            // Act
+            let ignored_score = score_path_quality(&ignored, takes_ignored_penalty(&ignored, &cfg()), &cfg());
+            let tracked_score = score_path_quality(&tracked, takes_ignored_penalty(&tracked, &cfg()), &cfg());
+
+            // Assert
+            assert_eq!(ignored_score - tracked_score, IGNORED_PENALTY);
+        }
+
+        #[test]
+        fn boosted_roots_rank_higher() {
+            // Arrange
+            let mut config = cfg();
+            config.scope.boosts.insert("/work/main".to_string(), 300);
+
+            // Act
+            let boosted = score_path_quality("/work/main/notes.md", false, &config);
+            let other = score_path_quality("/work/misc/notes.md", false, &config);
+
+            // Assert
+            assert_eq!(other - boosted, 300);
+        }
     }
 
     mod score_result {
@@ -1280,7 +3569,7 @@ mod tests {
         fn cfg() -> Config { Config::default() }
 
         fn make_result(path: &str, name: &str) -> SearchResult {
-            SearchResult { path: path.to_string(), name: name.to_string(), is_dir: false, icon: None }
+            SearchResult { path: path.to_string(), name: name.to_string(), is_dir: false, icon: None, score: None }
         }
 
         fn make_app(name: &str) -> SearchResult {
@@ -1293,39 +3582,39 @@ mod tests {
         #[test]
         fn exact_match_has_lowest_penalty() {
             let r = make_app("foo");
-            let score = score_result(&r, "foo", &FrequencyData::default(), &cfg());
+            let score = score_result(&r, "foo", &FrequencyData::default(), &cfg()).total;
             assert!(score < 50);
         }
 
         #[test]
         fn prefix_lower_than_contains() {
             let freq = FrequencyData::default();
-            let prefix_score = score_result(&make_app("foobar"), "foo", &freq, &cfg());
-            let contains_score = score_result(&make_app("xfoo"), "foo", &freq, &cfg());
+            let prefix_score = score_result(&make_app("foobar"), "foo", &freq, &cfg()).total;
+            let contains_score = score_result(&make_app("xfoo"), "foo", &freq, &cfg()).total;
             assert!(prefix_score < contains_score);
         }
 
         #[test]
         fn desktop_lower_than_folder() {
             let freq = FrequencyData::default();
-            let desktop_score = score_result(&make_app("foo"), "foo", &freq, &cfg());
-            let folder_score = score_result(&make_result("/some/path/foo", "foo"), "foo", &freq, &cfg());
+            let desktop_score = score_result(&make_app("foo"), "foo", &freq, &cfg()).total;
+            let folder_score = score_result(&make_result("/some/path/foo", "foo"), "foo", &freq, &cfg()).total;
             assert!(desktop_score < folder_score);
         }
 
         #[test]
         fn shorter_name_lower_score() {
             let freq = FrequencyData::default();
-            let short_score = score_result(&make_app("foob"), "foo", &freq, &cfg());
-            let long_score = score_result(&make_app("foobar"), "foo", &freq, &cfg());
+            let short_score = score_result(&make_app("foob"), "foo", &freq, &cfg()).total;
+            let long_score = score_result(&make_app("foobar"), "foo", &freq, &cfg()).total;
             assert!(short_score < long_score);
         }
 
         #[test]
         fn standard_path_lower_than_autostart() {
             let freq = FrequencyData::default();
-            let standard_score = score_result(&make_app("foo"), "foo", &freq, &cfg());
-            let autostart_score = score_result(&make_result("/etc/xdg/autostart/foo.desktop", "foo"), "foo", &freq, &cfg());
+            let standard_score = score_result(&make_app("foo"), "foo", &freq, &cfg()).total;
+            let autostart_score = score_result(&make_result("/etc/xdg/autostart/foo.desktop", "foo"), "foo", &freq, &cfg()).total;
             assert!(standard_score < autostart_score);
         }
 
@@ -1335,10 +3624,249 @@ mod tests {
             let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
             let mut freq = FrequencyData::default();
             freq.entries.insert(r.path.clone(), FrequencyEntry { count: 5, last_accessed: now });
-            let score_with_freq = score_result(&r, "foo", &freq, &cfg());
-            let score_without_freq = score_result(&r, "foo", &FrequencyData::default(), &cfg());
+            let score_with_freq = score_result(&r, "foo", &freq, &cfg()).total;
+            let score_without_freq = score_result(&r, "foo", &FrequencyData::default(), &cfg()).total;
             assert!(score_with_freq < score_without_freq);
         }
+
+        #[test]
+        fn words_in_name_beat_missing_words() {
+            let freq = FrequencyData::default();
+            let both = score_result(&make_result("/d/report-2024.pdf", "report-2024.pdf"), "report 2024", &freq, &cfg()).total;
+            let one = score_result(&make_result("/d/report-2023.pdf", "report-2023.pdf"), "report 2024", &freq, &cfg()).total;
+            assert!(both < one);
+        }
+
+        #[test]
+        fn words_in_name_beat_words_in_directories() {
+            // Arrange
+            let freq = FrequencyData::default();
+            let in_name = make_result("/d/docs/tax-report.pdf", "tax-report.pdf");
+            let in_dir = make_result("/d/tax/report.pdf", "report.pdf");
+
+            // Act
+            let name_score = score_result(&in_name, "tax report", &freq, &cfg()).total;
+            let dir_score = score_result(&in_dir, "tax report", &freq, &cfg()).total;
+
+            // Assert
+            assert!(name_score < dir_score);
+        }
+
+        #[test]
+        fn words_in_directories_beat_misses() {
+            // Arrange
+            let freq = FrequencyData::default();
+            let in_dir = make_result("/d/2024/report.pdf", "report.pdf");
+            let missing = make_result("/d/misc/report.pdf", "report.pdf");
+
+            // Act
+            let dir_score = score_result(&in_dir, "report 2024", &freq, &cfg()).total;
+            let missing_score = score_result(&missing, "report 2024", &freq, &cfg()).total;
+
+            // Assert
+            assert!(dir_score < missing_score);
+        }
+
+        #[test]
+        fn whole_query_match_still_wins() {
+            let freq = FrequencyData::default();
+            let exact = score_result(&make_app("annual report"), "annual report", &freq, &cfg()).total;
+            let reordered = score_result(&make_app("report annual"), "annual report", &freq, &cfg()).total;
+            assert!(exact < reordered);
+        }
+    }
+
+    mod match_penalty {
+        use super::*;
+
+        fn dirs(list: &[&str]) -> Vec<String> {
+            list.iter().map(|d| d.to_string()).collect()
+        }
+
+        #[test]
+        fn single_word_keeps_name_tiers() {
+            let config = Config::default();
+            assert_eq!(match_penalty("foo", &[], "foo", &config), config.exact_bonus);
+            assert_eq!(match_penalty("foobar", &[], "foo", &config), config.prefix_penalty);
+            assert_eq!(match_penalty("xfoo", &[], "foo", &config), config.contains_penalty);
+            assert_eq!(match_penalty("bar", &[], "foo", &config), NO_MATCH_PENALTY);
+        }
+
+        #[test]
+        fn averages_word_tiers() {
+            // Arrange
+            let config = Config::default();
+
+            // Act
+            let penalty = match_penalty("report-2024.pdf", &dirs(&["home", "me"]), "report 2024 tax", &config);
+
+            // Assert
+            assert_eq!(penalty, (config.prefix_penalty + config.contains_penalty + NO_MATCH_PENALTY) / 3);
+        }
+
+        #[test]
+        fn directory_hits_count_per_word() {
+            let config = Config::default();
+            let penalty = match_penalty("report.pdf", &dirs(&["finance", "2024"]), "2024", &config);
+            assert_eq!(penalty, DIR_MATCH_PENALTY);
+        }
+
+        #[test]
+        fn ignores_accents_and_normal_form() {
+            // Arrange
+            let config = Config::default();
+
+            // Assert
+            assert_eq!(match_penalty("Caf\u{e9}", &[], "cafe", &config), config.exact_bonus);
+            assert_eq!(match_penalty("Cafe\u{301}", &[], "caf\u{e9}", &config), config.exact_bonus);
+        }
+
+        #[test]
+        fn transliterates_when_enabled() {
+            // Arrange
+            let plain = Config::default();
+            let latin = Config { transliterate: true, ..Config::default() };
+
+            // Assert
+            assert_eq!(match_penalty("Москва.txt", &[], "moskva", &plain), NO_MATCH_PENALTY);
+            assert_eq!(match_penalty("Москва.txt", &[], "moskva", &latin), latin.prefix_penalty);
+            assert_eq!(match_penalty("北京.txt", &[], "beijing", &latin), latin.prefix_penalty);
+        }
+
+        #[test]
+        fn ignores_case_and_extra_spaces() {
+            let config = Config::default();
+            assert_eq!(match_penalty("Report 2024", &[], "  REPORT   2024 ", &config), config.exact_bonus);
+        }
+    }
+
+    mod selection_for {
+        use super::*;
+
+        fn results(count: usize) -> Vec<SearchResult> {
+            (0..count).map(|i| parse_search_result(&format!("/d/file{}.txt", i))).collect()
+        }
+
+        #[test]
+        fn records_query_candidates_and_uses() {
+            // Arrange
+            let mut freq = FrequencyData::default();
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            freq.entries.insert("/d/file1.txt".to_string(), FrequencyEntry { count: 2, last_accessed: now });
+
+            // Act
+            let selection = selection_for("file", &results(3), "/d/file1.txt", &freq, &Config::default()).unwrap();
+
+            // Assert
+            assert_eq!(selection.query, "file");
+            assert_eq!(selection.candidates.len(), 3);
+            assert!(selection.candidates[1].uses > 1.9);
+            assert_eq!(selection.candidates[0].uses, 0.0);
+        }
+
+        #[test]
+        fn keeps_chosen_result_beyond_the_cap() {
+            // Arrange
+            let chosen = format!("/d/file{}.txt", history::MAX_CANDIDATES + 5);
+
+            // Act
+            let selection = selection_for("file", &results(40), &chosen, &FrequencyData::default(), &Config::default()).unwrap();
+
+            // Assert
+            assert_eq!(selection.candidates.len(), history::MAX_CANDIDATES + 1);
+            assert_eq!(selection.candidates.last().unwrap().path, chosen);
+        }
+
+        #[test]
+        fn ignores_paths_outside_the_results() {
+            let selection = selection_for("file", &results(3), "/elsewhere", &FrequencyData::default(), &Config::default());
+            assert!(selection.is_none());
+        }
+    }
+
+    mod rank_results {
+        use super::*;
+
+        fn make_result(path: &str) -> SearchResult {
+            SearchResult { path: path.to_string(), name: extract_filename(path), is_dir: false, icon: None, score: None }
+        }
+
+        fn rank(query: &str, paths: &[&str]) -> Vec<String> {
+            let results = paths.iter().map(|p| make_result(p)).collect();
+            let parsed = query::parse(query);
+            rank_results(results, &parsed, &FrequencyData::default(), &Config::default(), SystemTime::now())
+                .into_iter()
+                .map(|r| r.path)
+                .collect()
+        }
+
+        #[test]
+        fn exact_phrase_requires_words_in_order() {
+            // Act
+            let ranked = rank(r#""annual report""#, &["/d/annual report.pdf", "/d/report annual.pdf", "/d/Annual Report 2024.odt"]);
+
+            // Assert
+            assert_eq!(ranked, vec!["/d/annual report.pdf", "/d/Annual Report 2024.odt"]);
+        }
+
+        #[test]
+        fn exact_phrase_ranks_like_plain_text() {
+            let ranked = rank(r#""notes""#, &["/d/old notes.md", "/d/notes.md"]);
+            assert_eq!(ranked, vec!["/d/notes.md", "/d/old notes.md"]);
+        }
+
+        #[test]
+        fn regex_matches_file_name() {
+            // Act
+            let ranked = rank(r"/^inv\d+\.pdf$/", &["/d/inv001.pdf", "/d/invoice.pdf", "/inv1.pdf/readme.txt"]);
+
+            // Assert
+            assert_eq!(ranked, vec!["/d/inv001.pdf"]);
+        }
+
+        #[test]
+        fn regex_with_slash_matches_whole_path() {
+            let ranked = rank(r"/src\/.*\.rs$/", &["/p/src/main.rs", "/p/tests/main.rs"]);
+            assert_eq!(ranked, vec!["/p/src/main.rs"]);
+        }
+
+        #[test]
+        fn regex_is_case_insensitive() {
+            assert_eq!(rank("/readme/", &["/p/README.md"]), vec!["/p/README.md"]);
+        }
+
+        #[test]
+        fn invalid_regex_is_plain_text() {
+            // Act
+            let parsed = query::parse("/foo(/");
+
+            // Assert
+            assert!(parsed.patterns.is_empty());
+            assert_eq!(parsed.text, "/foo(/");
+        }
+
+        #[test]
+        fn negation_drops_matching_paths() {
+            // Act
+            let ranked = rank("report !draft", &["/d/report.pdf", "/d/report-draft.pdf", "/d/Draft/report.odt"]);
+
+            // Assert
+            assert_eq!(ranked, vec!["/d/report.pdf"]);
+        }
+
+        #[test]
+        fn modes_combine() {
+            // Act
+            let ranked = rank(r#""tax return" /20\d\d/ !old ext:pdf"#, &[
+                "/d/tax return 2023.pdf",
+                "/d/tax return 2023.odt",
+                "/d/tax return.pdf",
+                "/old/tax return 2022.pdf",
+            ]);
+
+            // Assert
+            assert_eq!(ranked, vec!["/d/tax return 2023.pdf"]);
+        }
     }
 
     mod sort_by_relevance {
@@ -1347,7 +3875,7 @@ mod tests {
         fn cfg() -> Config { Config::default() }
 
         fn make_result(path: &str, name: &str) -> SearchResult {
-            SearchResult { path: path.to_string(), name: name.to_string(), is_dir: false, icon: None }
+            SearchResult { path: path.to_string(), name: name.to_string(), is_dir: false, icon: None, score: None }
         }
 
         fn make_app(name: &str) -> SearchResult {
@@ -1356,6 +3884,7 @@ mod tests {
                 name: name.to_string(),
                 is_dir: false,
                 icon: None,
+                score: None,
             }
         }
 
@@ -1399,6 +3928,19 @@ mod tests {
             assert!(sort_by_relevance(vec![], "test", &FrequencyData::default(), &cfg()).is_empty());
         }
 
+        #[test]
+        fn attaches_score_breakdown() {
+            // Act
+            let sorted = sort_by_relevance(vec![make_result("/d/foobar.txt", "foobar.txt")], "foo", &FrequencyData::default(), &cfg());
+
+            // Assert
+            let score = sorted[0].score.unwrap();
+            assert_eq!(score.match_penalty, cfg().prefix_penalty);
+            assert_eq!(score.length_penalty, 10);
+            assert_eq!(score.total, score.match_penalty + score.type_penalty + score.path_penalty + score.length_penalty - score.frequency_bonus);
+            assert_eq!(score.total, score_result(&sorted[0], "foo", &FrequencyData::default(), &cfg()).total);
+        }
+
         #[test]
         fn single_result_unchanged() {
             let results = vec![make_app("test")];
@@ -1473,7 +4015,7 @@ mod tests {
         use super::*;
 
         fn make_result(path: &str, name: &str) -> SearchResult {
-            SearchResult { path: path.to_string(), name: name.to_string(), is_dir: false, icon: None }
+            SearchResult { path: path.to_string(), name: name.to_string(), is_dir: false, icon: None, score: None }
         }
 
         fn make_app(name: &str) -> SearchResult {
@@ -1486,16 +4028,14 @@ mod tests {
             let app = make_app("foo");
             let file = make_result("/usr/share/docs/foo", "foo");
             let freq = FrequencyData::default();
-            let mut enabled = Config::default();
-            enabled.prefer_apps = true;
-            let mut disabled = Config::default();
-            disabled.prefer_apps = false;
+            let enabled = Config { prefer_apps: true, ..Config::default() };
+            let disabled = Config { prefer_apps: false, ..Config::default() };
 
             // Act
-            let app_enabled = score_result(&app, "foo", &freq, &enabled);
-            let file_enabled = score_result(&file, "foo", &freq, &enabled);
-            let app_disabled = score_result(&app, "foo", &freq, &disabled);
-            let file_disabled = score_result(&file, "foo", &freq, &disabled);
+            let app_enabled = score_result(&app, "foo", &freq, &enabled).total;
+            let file_enabled = score_result(&file, "foo", &freq, &enabled).total;
+            let app_disabled = score_result(&app, "foo", &freq, &disabled).total;
+            let file_disabled = score_result(&file, "foo", &freq, &disabled).total;
 
             // Assert
             let gap_enabled = file_enabled - app_enabled;
@@ -1509,16 +4049,14 @@ mod tests {
             // Arrange
             let visible = "/a/b/foo";
             let hidden = "/a/.hidden/foo";
-            let mut enabled = Config::default();
-            enabled.penalize_hidden = true;
-            let mut disabled = Config::default();
-            disabled.penalize_hidden = false;
+            let enabled = Config { penalize_hidden: true, ..Config::default() };
+            let disabled = Config { penalize_hidden: false, ..Config::default() };
 
             // Act
-            let visible_enabled = score_path_quality(visible, &enabled);
-            let hidden_enabled = score_path_quality(hidden, &enabled);
-            let visible_disabled = score_path_quality(visible, &disabled);
-            let hidden_disabled = score_path_quality(hidden, &disabled);
+            let visible_enabled = score_path_quality(visible, false, &enabled);
+            let hidden_enabled = score_path_quality(hidden, false, &enabled);
+            let visible_disabled = score_path_quality(visible, false, &disabled);
+            let hidden_disabled = score_path_quality(hidden, false, &disabled);
 
             // Assert
             let gap_enabled = hidden_enabled - visible_enabled;
@@ -1532,18 +4070,14 @@ mod tests {
             // Arrange
             let shallow = "/a/b/foo";
             let deep = "/a/b/c/d/e/f/foo";
-            let mut high_penalty = Config::default();
-            high_penalty.depth_penalty = 10;
-            high_penalty.penalize_hidden = false;
-            let mut no_penalty = Config::default();
-            no_penalty.depth_penalty = 0;
-            no_penalty.penalize_hidden = false;
+            let high_penalty = Config { depth_penalty: 10, penalize_hidden: false, ..Config::default() };
+            let no_penalty = Config { depth_penalty: 0, penalize_hidden: false, ..Config::default() };
 
             // Act
-            let shallow_high = score_path_quality(shallow, &high_penalty);
-            let deep_high = score_path_quality(deep, &high_penalty);
-            let shallow_none = score_path_quality(shallow, &no_penalty);
-            let deep_none = score_path_quality(deep, &no_penalty);
+            let shallow_high = score_path_quality(shallow, false, &high_penalty);
+            let deep_high = score_path_quality(deep, false, &high_penalty);
+            let shallow_none = score_path_quality(shallow, false, &no_penalty);
+            let deep_none = score_path_quality(deep, false, &no_penalty);
 
             // Assert
             let diff_high = deep_high - shallow_high;
@@ -1558,10 +4092,8 @@ mod tests {
             let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
             let mut freq = FrequencyData::default();
             freq.entries.insert("/path".to_string(), FrequencyEntry { count: 5, last_accessed: now });
-            let mut high_bonus = Config::default();
-            high_bonus.frequency_bonus = 1000;
-            let mut no_bonus = Config::default();
-            no_bonus.frequency_bonus = 0;
+            let high_bonus = Config { frequency_bonus: 1000, ..Config::default() };
+            let no_bonus = Config { frequency_bonus: 0, ..Config::default() };
 
             // Act
             let bonus_high = calc_frequency_bonus("/path", &freq, &high_bonus);
@@ -1597,21 +4129,15 @@ mod tests {
             let prefix = make_app("foobar");
             let contains = make_app("xfoox");
             let freq = FrequencyData::default();
-            let mut normal = Config::default();
-            normal.exact_bonus = 0;
-            normal.prefix_penalty = 50;
-            normal.contains_penalty = 100;
-            let mut reversed = Config::default();
-            reversed.exact_bonus = 100;
-            reversed.prefix_penalty = 50;
-            reversed.contains_penalty = 0;
+            let normal = Config { exact_bonus: 0, prefix_penalty: 50, contains_penalty: 100, ..Config::default() };
+            let reversed = Config { exact_bonus: 100, prefix_penalty: 50, contains_penalty: 0, ..Config::default() };
 
             // Act
-            let exact_normal = score_result(&exact, "foo", &freq, &normal);
-            let prefix_normal = score_result(&prefix, "foo", &freq, &normal);
-            let contains_normal = score_result(&contains, "foo", &freq, &normal);
-            let exact_reversed = score_result(&exact, "foo", &freq, &reversed);
-            let contains_reversed = score_result(&contains, "foo", &freq, &reversed);
+            let exact_normal = score_result(&exact, "foo", &freq, &normal).total;
+            let prefix_normal = score_result(&prefix, "foo", &freq, &normal).total;
+            let contains_normal = score_result(&contains, "foo", &freq, &normal).total;
+            let exact_reversed = score_result(&exact, "foo", &freq, &reversed).total;
+            let contains_reversed = score_result(&contains, "foo", &freq, &reversed).total;
 
             // Assert
             assert!(exact_normal < prefix_normal, "normal: exact < prefix");
@@ -1627,18 +4153,14 @@ mod tests {
             let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
             let mut freq = FrequencyData::default();
             freq.entries.insert(frequent_hidden.path.clone(), FrequencyEntry { count: 20, last_accessed: now });
-            let mut favor_frequency = Config::default();
-            favor_frequency.frequency_bonus = 2000;
-            favor_frequency.penalize_hidden = false;
-            let mut favor_visible = Config::default();
-            favor_visible.frequency_bonus = 0;
-            favor_visible.penalize_hidden = true;
-
-            // Act
-            let hidden_freq = score_result(&frequent_hidden, "foo", &freq, &favor_frequency);
-            let visible_freq = score_result(&rare_visible, "foo", &freq, &favor_frequency);
-            let hidden_vis = score_result(&frequent_hidden, "foo", &freq, &favor_visible);
-            let visible_vis = score_result(&rare_visible, "foo", &freq, &favor_visible);
+            let favor_frequency = Config { frequency_bonus: 2000, penalize_hidden: false, ..Config::default() };
+            let favor_visible = Config { frequency_bonus: 0, penalize_hidden: true, ..Config::default() };
+
+            // Act
+            let hidden_freq = score_result(&frequent_hidden, "foo", &freq, &favor_frequency).total;
+            let visible_freq = score_result(&rare_visible, "foo", &freq, &favor_frequency).total;
+            let hidden_vis = score_result(&frequent_hidden, "foo", &freq, &favor_visible).total;
+            let visible_vis = score_result(&rare_visible, "foo", &freq, &favor_visible).total;
 
             // Assert
             assert!(hidden_freq < visible_freq, "favor frequency: hidden ({}) should beat visible ({})", hidden_freq, visible_freq);
@@ -0,0 +1,88 @@
+//! Folding for names and queries, so matching ignores case, accents and whether a
+//! filename was stored composed (NFC) or decomposed (NFD, as on macOS drives).
+
+use deunicode::deunicode_char;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Lowercases, decomposes (NFKD) and drops combining marks: "Café", "CAFE" and a
+/// decomposed "café" all become "cafe".
+pub fn fold(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.to_lowercase().nfkd().filter(|c| !is_combining_mark(*c)) {
+        // Full case folding; `to_lowercase` keeps "ß".
+        if c == 'ß' { out.push_str("ss") } else { out.push(c) }
+    }
+    out
+}
+
+/// [`fold`], then spells other scripts in ASCII: "Москва" becomes "moskva" and
+/// "北京" becomes "beijing". Characters without a transliteration are kept.
+pub fn transliterate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in fold(text).chars() {
+        match deunicode_char(c) {
+            Some(ascii) if !c.is_ascii() && !ascii.trim().is_empty() => {
+                out.push_str(&ascii.trim().to_lowercase());
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// [`transliterate`] when `enabled`, [`fold`] otherwise.
+pub fn fold_with(text: &str, enabled: bool) -> String {
+    if enabled { transliterate(text) } else { fold(text) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod fold {
+        use super::*;
+
+        #[test]
+        fn strips_accents_and_case() {
+            assert_eq!(fold("Café.TXT"), "cafe.txt");
+            assert_eq!(fold("Ångström"), "angstrom");
+        }
+
+        #[test]
+        fn composed_and_decomposed_forms_agree() {
+            // Arrange
+            let composed = "R\u{e9}sum\u{e9}.pdf";
+            let decomposed = "Re\u{301}sume\u{301}.pdf";
+
+            // Assert
+            assert_eq!(fold(composed), fold(decomposed));
+            assert_eq!(fold(decomposed), "resume.pdf");
+        }
+
+        #[test]
+        fn applies_compatibility_mappings() {
+            assert_eq!(fold("ﬁle"), "file");
+            assert_eq!(fold("Straße"), "strasse");
+        }
+
+        #[test]
+        fn leaves_other_scripts_alone() {
+            assert_eq!(fold("Москва"), "москва");
+        }
+    }
+
+    mod transliterate {
+        use super::*;
+
+        #[test]
+        fn spells_cyrillic_in_latin() {
+            assert_eq!(transliterate("Москва.txt"), "moskva.txt");
+        }
+
+        #[test]
+        fn joins_chinese_syllables() {
+            assert_eq!(transliterate("北京 notes"), "beijing notes");
+        }
+    }
+}
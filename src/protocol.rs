@@ -0,0 +1,498 @@
+//! Newline-delimited JSON protocol spoken over the daemon socket.
+//!
+//! Each request is one line, e.g. `{"id": 1, "command": "query", "query": "foo"}`,
+//! and gets one reply line carrying the same `id`. Bare words such as `show` are
+//! accepted as shorthand for `{"command": "show"}`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{FileTypeExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SOCKET_NAME: &str = "qol-launcher.sock";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Request {
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub id: Value,
+    #[serde(flatten)]
+    pub command: Command,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    #[default]
+    All,
+    Apps,
+    Files,
+}
+
+impl SearchMode {
+    pub fn accepts(self, path: &str) -> bool {
+        match self {
+            SearchMode::All => true,
+            SearchMode::Apps => path.ends_with(".desktop"),
+            SearchMode::Files => !path.ends_with(".desktop"),
+        }
+    }
+}
+
+impl std::str::FromStr for SearchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(Value::from(s)).map_err(|_| format!("unknown mode: {} (expected all, apps or files)", s))
+    }
+}
+
+/// What the window opens with: prefilled text and the search mode.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ShowOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<SearchMode>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Command {
+    Show(ShowOptions),
+    Hide,
+    Toggle,
+    Query {
+        query: String,
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        mode: SearchMode,
+        /// Include each result's score breakdown.
+        #[serde(default)]
+        explain: bool,
+    },
+    Execute { path: String, action: String },
+    ReloadConfig,
+    Status,
+    Version,
+    Kill,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Response {
+    #[serde(default)]
+    pub id: Value,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub result: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self { id, ok: true, result, error: None }
+    }
+
+    pub fn failure(id: Value, error: impl Into<String>) -> Self {
+        Self { id, ok: false, result: Value::Null, error: Some(error.into()) }
+    }
+}
+
+pub fn parse_request(line: &str) -> Result<Request, String> {
+    let line = line.trim();
+    if !line.starts_with('{') {
+        return serde_json::from_value(serde_json::json!({ "command": line }))
+            .map_err(|_| format!("unknown command: {}", line));
+    }
+    serde_json::from_str(line).map_err(|e| e.to_string())
+}
+
+/// Best-effort id of a request that failed to parse, so the error reply can still be matched.
+pub fn request_id(line: &str) -> Value {
+    serde_json::from_str::<Value>(line)
+        .ok()
+        .and_then(|v| v.get("id").cloned())
+        .unwrap_or(Value::Null)
+}
+
+pub fn encode<T: Serialize>(message: &T) -> String {
+    let mut line = serde_json::to_string(message).unwrap_or_default();
+    line.push('\n');
+    line
+}
+
+/// `$XDG_RUNTIME_DIR/qol-launcher.sock`, or a per-user name in `/tmp` when the runtime dir is unset.
+pub fn socket_path_for(runtime_dir: Option<OsString>, uid: u32) -> PathBuf {
+    match runtime_dir.filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join(SOCKET_NAME),
+        None => std::env::temp_dir().join(format!("qol-launcher-{}.sock", uid)),
+    }
+}
+
+pub fn socket_path() -> PathBuf {
+    socket_path_for(std::env::var_os("XDG_RUNTIME_DIR"), current_uid())
+}
+
+pub fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail.
+    unsafe { libc::geteuid() }
+}
+
+/// Holds an exclusive lock next to the socket for the daemon's lifetime.
+/// `None` means another daemon owns the socket. In a shared directory like `/tmp`
+/// another user could plant the lock file first, so symlinks are not followed and a
+/// file we do not own is refused.
+pub fn lock_daemon(socket: &Path) -> io::Result<Option<File>> {
+    let path = socket.with_extension("lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(&path)?;
+    let meta = file.metadata()?;
+    if !meta.is_file() || meta.uid() != current_uid() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is not owned by us; set XDG_RUNTIME_DIR or remove it", path.display()),
+        ));
+    }
+    // SAFETY: the descriptor is owned by `file` and stays open for the call.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        let err = io::Error::last_os_error();
+        return match err.kind() {
+            io::ErrorKind::WouldBlock => Ok(None),
+            _ => Err(err),
+        };
+    }
+    Ok(Some(file))
+}
+
+/// Binds the socket with mode 0600. Only call while holding the daemon lock: a leftover
+/// socket is then stale, and is removed if it is ours.
+pub fn bind(socket: &Path) -> io::Result<UnixListener> {
+    match fs::symlink_metadata(socket) {
+        Ok(meta) if !meta.file_type().is_socket() || meta.uid() != current_uid() => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not our socket", socket.display()),
+            ));
+        }
+        Ok(_) => fs::remove_file(socket)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let listener = UnixListener::bind(socket)?;
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+#[cfg(target_os = "linux")]
+pub fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let mut cred = libc::ucred { pid: 0, uid: 0, gid: 0 };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: `cred` and `len` describe a valid ucred buffer for SO_PEERCRED.
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if rc != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(cred.uid)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let (mut uid, mut gid) = (0, 0);
+    // SAFETY: getpeereid only writes to the two provided ids.
+    if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(uid)
+}
+
+/// Sends one request to the daemon and waits for its reply.
+pub fn send(socket: &Path, command: Command, timeout: Duration) -> io::Result<Response> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.write_all(encode(&Request { id: Value::from(1), command }).as_bytes())?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod parse_request {
+        use super::*;
+
+        #[test]
+        fn parses_command_with_fields() {
+            // Act
+            let request = parse_request(r#"{"id": 7, "command": "query", "query": "foo", "limit": 3}"#).unwrap();
+
+            // Assert
+            assert_eq!(request.id, Value::from(7));
+            assert_eq!(request.command, Command::Query { query: "foo".to_string(), limit: Some(3), mode: SearchMode::All, explain: false });
+        }
+
+        #[test]
+        fn accepts_string_ids() {
+            let request = parse_request(r#"{"id": "a", "command": "status"}"#).unwrap();
+            assert_eq!(request.id, Value::from("a"));
+        }
+
+        #[test]
+        fn id_is_optional() {
+            let request = parse_request(r#"{"command": "toggle"}"#).unwrap();
+            assert_eq!(request.id, Value::Null);
+            assert_eq!(request.command, Command::Toggle);
+        }
+
+        #[test]
+        fn accepts_bare_words() {
+            assert_eq!(parse_request("show\n").unwrap().command, Command::Show(ShowOptions::default()));
+            assert_eq!(parse_request("reload-config").unwrap().command, Command::ReloadConfig);
+        }
+
+        #[test]
+        fn rejects_unknown_bare_word() {
+            assert_eq!(parse_request("explode").unwrap_err(), "unknown command: explode");
+        }
+
+        #[test]
+        fn rejects_missing_fields() {
+            assert!(parse_request(r#"{"command": "execute", "path": "/a"}"#).is_err());
+        }
+    }
+
+    mod show_options {
+        use super::*;
+
+        #[test]
+        fn show_carries_query_and_mode() {
+            // Act
+            let request = parse_request(r#"{"command": "show", "query": "~/Projects/", "mode": "apps"}"#).unwrap();
+
+            // Assert
+            let expected = ShowOptions { query: Some("~/Projects/".to_string()), mode: Some(SearchMode::Apps) };
+            assert_eq!(request.command, Command::Show(expected));
+        }
+
+        #[test]
+        fn rejects_unknown_mode() {
+            assert!(parse_request(r#"{"command": "show", "mode": "music"}"#).is_err());
+        }
+
+        #[test]
+        fn round_trips_through_encode() {
+            let command = Command::Show(ShowOptions { query: Some("a b".to_string()), mode: None });
+            let request = Request { id: Value::Null, command: command.clone() };
+            assert_eq!(parse_request(&encode(&request)).unwrap().command, command);
+        }
+    }
+
+    mod search_mode {
+        use super::*;
+
+        #[test]
+        fn parses_names() {
+            assert_eq!("apps".parse(), Ok(SearchMode::Apps));
+            assert_eq!("files".parse(), Ok(SearchMode::Files));
+            assert!("music".parse::<SearchMode>().is_err());
+        }
+
+        #[test]
+        fn filters_by_desktop_entry() {
+            assert!(SearchMode::Apps.accepts("/usr/share/applications/firefox.desktop"));
+            assert!(!SearchMode::Apps.accepts("/home/me/notes.md"));
+            assert!(SearchMode::Files.accepts("/home/me/notes.md"));
+            assert!(SearchMode::All.accepts("/usr/share/applications/firefox.desktop"));
+        }
+    }
+
+    mod request_id {
+        use super::*;
+
+        #[test]
+        fn recovers_id_from_invalid_request() {
+            assert_eq!(request_id(r#"{"id": 3, "command": "nope"}"#), Value::from(3));
+        }
+
+        #[test]
+        fn null_for_non_json() {
+            assert_eq!(request_id("garbage"), Value::Null);
+        }
+    }
+
+    mod socket_path_for {
+        use super::*;
+
+        #[test]
+        fn uses_runtime_dir() {
+            let path = socket_path_for(Some("/run/user/1000".into()), 1000);
+            assert_eq!(path, PathBuf::from("/run/user/1000/qol-launcher.sock"));
+        }
+
+        #[test]
+        fn falls_back_to_per_user_name() {
+            // Act
+            let unset = socket_path_for(None, 1000);
+            let empty = socket_path_for(Some("".into()), 1000);
+
+            // Assert
+            assert!(unset.ends_with("qol-launcher-1000.sock"));
+            assert_eq!(unset, empty);
+        }
+    }
+
+    mod bind {
+        use super::*;
+        use tempfile::tempdir;
+
+        #[test]
+        fn socket_is_private() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("test.sock");
+
+            // Act
+            let _listener = bind(&socket).unwrap();
+
+            // Assert
+            let mode = fs::metadata(&socket).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        #[test]
+        fn replaces_stale_socket() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("test.sock");
+            drop(UnixListener::bind(&socket).unwrap());
+
+            // Act
+            let listener = bind(&socket);
+
+            // Assert
+            assert!(listener.is_ok());
+            assert!(UnixStream::connect(&socket).is_ok());
+        }
+
+        #[test]
+        fn refuses_to_replace_regular_file() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("test.sock");
+            fs::write(&socket, "data").unwrap();
+
+            // Act
+            let result = bind(&socket);
+
+            // Assert
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+            assert_eq!(fs::read_to_string(&socket).unwrap(), "data");
+        }
+    }
+
+    mod lock_daemon {
+        use super::*;
+        use tempfile::tempdir;
+
+        #[test]
+        fn second_lock_fails_while_first_is_held() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("test.sock");
+            let first = lock_daemon(&socket).unwrap();
+
+            // Act
+            let second = lock_daemon(&socket).unwrap();
+
+            // Assert
+            assert!(first.is_some());
+            assert!(second.is_none());
+        }
+
+        #[test]
+        fn refuses_symlinked_lock_file() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("test.sock");
+            fs::write(dir.path().join("target"), "").unwrap();
+            std::os::unix::fs::symlink(dir.path().join("target"), dir.path().join("test.lock")).unwrap();
+
+            // Act
+            let result = lock_daemon(&socket);
+
+            // Assert
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn lock_is_released_on_drop() {
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("test.sock");
+            drop(lock_daemon(&socket).unwrap());
+            assert!(lock_daemon(&socket).unwrap().is_some());
+        }
+    }
+
+    mod peer_uid {
+        use super::*;
+
+        #[test]
+        fn reports_own_uid_for_socket_pair() {
+            let (a, _b) = UnixStream::pair().unwrap();
+            assert_eq!(peer_uid(&a).unwrap(), current_uid());
+        }
+    }
+
+    mod encode {
+        use super::*;
+
+        #[test]
+        fn omits_empty_fields() {
+            // Arrange
+            let response = Response::success(Value::from(1), Value::Null);
+
+            // Act
+            let line = encode(&response);
+
+            // Assert
+            assert_eq!(line, "{\"id\":1,\"ok\":true}\n");
+        }
+
+        #[test]
+        fn round_trips_request() {
+            // Arrange
+            let request = Request {
+                id: Value::from(2),
+                command: Command::Execute { path: "/a".to_string(), action: "open".to_string() },
+            };
+
+            // Act
+            let parsed = parse_request(&encode(&request)).unwrap();
+
+            // Assert
+            assert_eq!(parsed, request);
+        }
+    }
+}
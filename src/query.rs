@@ -0,0 +1,676 @@
+//! Search operators typed into the query, such as `ext:pdf` or `size:>100M`, and the
+//! `"exact phrase"`, `/regex/` and `!term` match modes.
+//!
+//! Operators are split off before the backend runs. The ones a backend can use are
+//! passed to it as environment variables, and all of them are checked again on the
+//! results, so a backend that ignores a variable still returns the right files.
+//! A token that is not a known operator with a valid value stays part of the text.
+
+use crate::normalize;
+use crate::scope;
+use crate::SearchResult;
+use regex::{Regex, RegexBuilder};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    File,
+    Dir,
+    App,
+}
+
+/// `<` or `>` in `modified:` and `size:`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    Below,
+    Above,
+}
+
+impl Bound {
+    fn holds<T: PartialOrd>(self, value: T, limit: T) -> bool {
+        match self {
+            Bound::Below => value < limit,
+            Bound::Above => value > limit,
+        }
+    }
+}
+
+/// A `/regex/` term, matched case-insensitively against the file name, or against
+/// the whole path when the pattern contains an escaped slash (`\/`).
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub regex: Regex,
+    pub whole_path: bool,
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+    }
+}
+
+impl Pattern {
+    fn new(source: &str) -> Option<Self> {
+        let regex = RegexBuilder::new(source).case_insensitive(true).build().ok()?;
+        Some(Self { regex, whole_path: source.contains("\\/") })
+    }
+
+    fn is_match(&self, result: &SearchResult) -> bool {
+        self.regex.is_match(if self.whole_path { &result.path } else { &result.name })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Phrase(String),
+    Regex(String),
+}
+
+/// Index of the `/` closing a regex opened at `start`: the next unescaped slash that
+/// ends a word. `/usr/bin` therefore stays a word while `/^inv.*pdf$/` is a regex.
+fn regex_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '/' if i > start + 1 && chars.get(i + 1).is_none_or(|c| c.is_whitespace()) => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Splits a query on whitespace, keeping `"quoted phrases"` and `/regexes/` whole.
+/// An unclosed quote runs to the end, so a phrase still matches while it is typed.
+fn tokenize(query: &str) -> Vec<Token> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if chars[i] == '"' {
+            let end = chars[i + 1..].iter().position(|&c| c == '"').map_or(chars.len(), |p| i + 1 + p);
+            tokens.push(Token::Phrase(chars[i + 1..end].iter().collect()));
+            i = end + 1;
+            continue;
+        }
+        if chars[i] == '/' {
+            if let Some(end) = regex_end(&chars, i) {
+                tokens.push(Token::Regex(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+                continue;
+            }
+        }
+        let end = chars[i..].iter().position(|c| c.is_whitespace()).map_or(chars.len(), |p| i + p);
+        tokens.push(Token::Word(chars[i..end].iter().collect()));
+        i = end;
+    }
+    tokens
+}
+
+/// The longest run of letters or digits every match of `source` must contain, so the
+/// backend can be asked for it instead of the regex. `None` when nothing is required.
+pub fn required_literal(source: &str) -> Option<String> {
+    if source.contains('|') {
+        return None;
+    }
+    let mut best = String::new();
+    let mut run = String::new();
+    let mut depth = 0;
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+                run.clear();
+            }
+            '[' => {
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+                run.clear();
+            }
+            '(' => {
+                depth += 1;
+                run.clear();
+            }
+            ')' => {
+                depth -= 1;
+                run.clear();
+            }
+            c if depth == 0 && (c.is_alphanumeric() || c == '-' || c == '_') => {
+                run.push(c);
+                if matches!(chars.peek(), Some('?' | '*' | '{')) {
+                    run.pop();
+                }
+                if run.chars().count() > best.chars().count() {
+                    best = run.clone();
+                }
+                if matches!(chars.peek(), Some('?' | '*' | '{')) {
+                    run.clear();
+                }
+            }
+            _ => run.clear(),
+        }
+    }
+    (best.chars().count() >= 2).then_some(best)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// The query without its operators, negations and regexes; quoted phrases are
+    /// included without their quotes, and each regex adds its [`required_literal`].
+    pub text: String,
+    /// `"exact phrase"` terms that must appear in the file name as written, folded
+    /// by [`normalize::fold`] like the names they are checked against.
+    pub phrases: Vec<String>,
+    pub patterns: Vec<Pattern>,
+    /// `!term` words, folded; a path containing any of them is dropped.
+    pub excluded: Vec<String>,
+    pub extensions: Vec<String>,
+    pub kind: Option<Kind>,
+    pub location: Option<String>,
+    /// Age of the last modification.
+    pub modified: Option<(Bound, Duration)>,
+    pub size: Option<(Bound, u64)>,
+}
+
+/// Splits a leading `<` or `>` off `value`, using `default` when there is none.
+fn bound(value: &str, default: Bound) -> (Bound, &str) {
+    if let Some(rest) = value.strip_prefix('<') {
+        (Bound::Below, rest)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (Bound::Above, rest)
+    } else {
+        (default, value)
+    }
+}
+
+/// Splits `7d` into `7` and `d`.
+fn number_and_unit(value: &str) -> Option<(u64, String)> {
+    let digits = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let number = value[..digits].parse().ok()?;
+    Some((number, value[digits..].to_lowercase()))
+}
+
+/// `7d`, `12h`, `30m`, `2w` or `1y`.
+pub fn parse_age(value: &str) -> Option<Duration> {
+    let (number, unit) = number_and_unit(value)?;
+    let seconds = match unit.as_str() {
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        "y" => 365 * 86_400,
+        _ => return None,
+    };
+    Some(Duration::from_secs(number * seconds))
+}
+
+/// `500`, `10K`, `100M` or `2G`, in binary units; a trailing `B` is allowed.
+pub fn parse_size(value: &str) -> Option<u64> {
+    let (number, unit) = number_and_unit(value)?;
+    let factor: u64 = match unit.trim_end_matches('b') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return None,
+    };
+    number.checked_mul(factor)
+}
+
+fn parse_kind(value: &str) -> Option<Kind> {
+    match value.to_lowercase().as_str() {
+        "file" | "f" => Some(Kind::File),
+        "dir" | "d" | "folder" => Some(Kind::Dir),
+        "app" | "a" => Some(Kind::App),
+        _ => None,
+    }
+}
+
+impl ParsedQuery {
+    /// Applies one `operator:value` token; false when it is not a valid operator.
+    fn apply(&mut self, operator: &str, value: &str, home: &Path) -> bool {
+        match operator {
+            "ext" if !value.is_empty() => {
+                self.extensions.extend(value.split(',')
+                    .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                    .filter(|ext| !ext.is_empty()));
+            }
+            "type" => match parse_kind(value) {
+                Some(kind) => self.kind = Some(kind),
+                None => return false,
+            },
+            "in" if !value.is_empty() => {
+                self.location = Some(scope::expand_home_in(value, home).trim_end_matches('/').to_string());
+            }
+            "app" => {
+                self.kind = Some(Kind::App);
+                self.push_text(value);
+            }
+            "modified" => {
+                let (bound, age) = bound(value, Bound::Below);
+                match parse_age(age) {
+                    Some(age) => self.modified = Some((bound, age)),
+                    None => return false,
+                }
+            }
+            "size" => {
+                let (bound, size) = bound(value, Bound::Above);
+                match parse_size(size) {
+                    Some(size) => self.size = Some((bound, size)),
+                    None => return false,
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn push_text(&mut self, word: &str) {
+        if word.is_empty() {
+            return;
+        }
+        if !self.text.is_empty() {
+            self.text.push(' ');
+        }
+        self.text.push_str(word);
+    }
+
+    /// Why the file backends will return nothing: a regex without a plain-text run
+    /// leaves them no words to look up, and nothing else narrows the index.
+    pub fn backend_note(&self) -> Option<&'static str> {
+        let blind = self.text.is_empty() && !self.patterns.is_empty()
+            && self.extensions.is_empty() && self.location.is_none() && self.kind != Some(Kind::App);
+        blind.then_some("This regex has no plain text for the file index to look up. Add a word, ext: or in: to search files.")
+    }
+
+    pub fn has_filters(&self) -> bool {
+        !self.extensions.is_empty() || self.kind.is_some() || self.location.is_some()
+            || self.modified.is_some() || self.size.is_some()
+            || !self.patterns.is_empty() || !self.excluded.is_empty()
+    }
+
+    /// Phrases must occur in the name. An excluded term rules out a name containing it
+    /// or a directory named exactly that, so `!test` keeps files under `tests/`.
+    fn matches_terms(&self, result: &SearchResult) -> bool {
+        let name = normalize::fold(&result.name);
+        let dirs: Vec<String> = match Path::new(&result.path).parent() {
+            Some(parent) if !self.excluded.is_empty() => parent.components()
+                .map(|c| normalize::fold(&c.as_os_str().to_string_lossy()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.phrases.iter().all(|phrase| name.contains(phrase.as_str()))
+            && self.patterns.iter().all(|pattern| pattern.is_match(result))
+            && !self.excluded.iter().any(|term| name.contains(term.as_str()) || dirs.contains(term))
+    }
+
+    /// Environment for the backend scripts: `LAUNCHER_EXT` (comma-separated),
+    /// `LAUNCHER_IN` and `LAUNCHER_APPS_ONLY`.
+    pub fn backend_env(&self) -> Vec<(&'static str, String)> {
+        let mut env = Vec::new();
+        if !self.extensions.is_empty() {
+            env.push(("LAUNCHER_EXT", self.extensions.join(",")));
+        }
+        if let Some(location) = &self.location {
+            env.push(("LAUNCHER_IN", location.clone()));
+        }
+        if self.kind == Some(Kind::App) {
+            env.push(("LAUNCHER_APPS_ONLY", "1".to_string()));
+        }
+        env
+    }
+
+    /// Checks a result against every operator; `now` is the reference for `modified:`.
+    pub fn matches(&self, result: &SearchResult, now: SystemTime) -> bool {
+        let path = Path::new(&result.path);
+        let is_app = result.path.ends_with(".desktop");
+        let kind_ok = match self.kind {
+            None => true,
+            Some(Kind::App) => is_app,
+            Some(Kind::Dir) => result.is_dir,
+            Some(Kind::File) => !result.is_dir && !is_app,
+        };
+        let ext_ok = self.extensions.is_empty() || path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .is_some_and(|ext| self.extensions.contains(&ext));
+        let location_ok = self.location.as_ref().is_none_or(|root| scope::is_under(&result.path, root));
+        if !(kind_ok && ext_ok && location_ok && self.matches_terms(result)) {
+            return false;
+        }
+        if self.modified.is_none() && self.size.is_none() {
+            return true;
+        }
+
+        let Ok(meta) = std::fs::metadata(path) else { return false };
+        let modified_ok = self.modified.is_none_or(|(bound, limit)| {
+            meta.modified().ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| bound.holds(age, limit))
+        });
+        let size_ok = self.size.is_none_or(|(bound, limit)| bound.holds(meta.len(), limit));
+        modified_ok && size_ok
+    }
+}
+
+pub fn parse_in(query: &str, home: &Path) -> ParsedQuery {
+    let mut parsed = ParsedQuery::default();
+    for token in tokenize(query) {
+        match token {
+            Token::Phrase(phrase) => {
+                let phrase = phrase.trim().to_lowercase();
+                parsed.push_text(&phrase);
+                if !phrase.is_empty() {
+                    parsed.phrases.push(normalize::fold(&phrase));
+                }
+            }
+            Token::Regex(source) => match Pattern::new(&source) {
+                Some(pattern) => {
+                    parsed.push_text(&required_literal(&source).unwrap_or_default());
+                    parsed.patterns.push(pattern);
+                }
+                None => parsed.push_text(&format!("/{}/", source)),
+            },
+            Token::Word(word) => {
+                if let Some(term) = word.strip_prefix('!').filter(|t| !t.is_empty()) {
+                    parsed.excluded.push(normalize::fold(term));
+                    continue;
+                }
+                let applied = word.split_once(':')
+                    .is_some_and(|(operator, value)| parsed.apply(&operator.to_lowercase(), value, home));
+                if !applied {
+                    parsed.push_text(&word);
+                }
+            }
+        }
+    }
+    parsed
+}
+
+pub fn parse(query: &str) -> ParsedQuery {
+    parse_in(query, &dirs::home_dir().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn parse_test(query: &str) -> ParsedQuery {
+        parse_in(query, Path::new("/home/me"))
+    }
+
+    fn result(path: &str, is_dir: bool) -> SearchResult {
+        SearchResult { path: path.to_string(), name: String::new(), is_dir, icon: None, score: None }
+    }
+
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn plain_query_is_all_text() {
+            assert_eq!(parse_test("tax report"), ParsedQuery { text: "tax report".to_string(), ..Default::default() });
+        }
+
+        #[test]
+        fn splits_operators_from_text() {
+            // Act
+            let parsed = parse_test("ext:pdf invoice in:~/Documents");
+
+            // Assert
+            assert_eq!(parsed.text, "invoice");
+            assert_eq!(parsed.extensions, vec!["pdf"]);
+            assert_eq!(parsed.location.as_deref(), Some("/home/me/Documents"));
+        }
+
+        #[test]
+        fn extensions_accumulate() {
+            assert_eq!(parse_test("ext:.PDF,docx ext:odt").extensions, vec!["pdf", "docx", "odt"]);
+        }
+
+        #[test]
+        fn app_operator_sets_kind_and_keeps_value() {
+            // Act
+            let parsed = parse_test("app:term");
+
+            // Assert
+            assert_eq!(parsed.kind, Some(Kind::App));
+            assert_eq!(parsed.text, "term");
+        }
+
+        #[test]
+        fn reads_type() {
+            assert_eq!(parse_test("type:dir proj").kind, Some(Kind::Dir));
+            assert_eq!(parse_test("type:file").kind, Some(Kind::File));
+        }
+
+        #[test]
+        fn reads_modified_and_size_bounds() {
+            // Act
+            let parsed = parse_test("modified:<7d size:>100M report");
+
+            // Assert
+            assert_eq!(parsed.modified, Some((Bound::Below, Duration::from_secs(7 * 86_400))));
+            assert_eq!(parsed.size, Some((Bound::Above, 100 << 20)));
+            assert_eq!(parsed.text, "report");
+        }
+
+        #[test]
+        fn bounds_have_defaults() {
+            // Act
+            let parsed = parse_test("modified:2h size:1k");
+
+            // Assert
+            assert_eq!(parsed.modified, Some((Bound::Below, Duration::from_secs(7200))));
+            assert_eq!(parsed.size, Some((Bound::Above, 1024)));
+        }
+
+        #[test]
+        fn invalid_operators_stay_text() {
+            // Act
+            let parsed = parse_test("size:lots type:song http://example.com C:foo");
+
+            // Assert
+            assert_eq!(parsed.text, "size:lots type:song http://example.com C:foo");
+            assert!(!parsed.has_filters());
+        }
+    }
+
+    mod tokenize {
+        use super::*;
+
+        #[test]
+        fn keeps_phrases_and_regexes_whole() {
+            // Act
+            let tokens = tokenize(r#"tax "annual report" /^inv.*\.pdf$/ 2024"#);
+
+            // Assert
+            assert_eq!(tokens, vec![
+                Token::Word("tax".to_string()),
+                Token::Phrase("annual report".to_string()),
+                Token::Regex(r"^inv.*\.pdf$".to_string()),
+                Token::Word("2024".to_string()),
+            ]);
+        }
+
+        #[test]
+        fn paths_are_words() {
+            assert_eq!(tokenize("/usr/bin"), vec![Token::Word("/usr/bin".to_string())]);
+        }
+
+        #[test]
+        fn unclosed_quote_runs_to_end() {
+            assert_eq!(tokenize(r#""annual rep"#), vec![Token::Phrase("annual rep".to_string())]);
+        }
+
+        #[test]
+        fn escaped_slash_does_not_close_regex() {
+            assert_eq!(tokenize(r"/src\/.*rs/"), vec![Token::Regex(r"src\/.*rs".to_string())]);
+        }
+    }
+
+    mod required_literal {
+        use super::*;
+
+        #[test]
+        fn picks_longest_required_run() {
+            assert_eq!(required_literal(r"^inv.*\.pdf$").as_deref(), Some("inv"));
+            assert_eq!(required_literal(r"report_\d{4}").as_deref(), Some("report_"));
+        }
+
+        #[test]
+        fn drops_optional_characters() {
+            assert_eq!(required_literal("colou?r").as_deref(), Some("colo"));
+            assert_eq!(required_literal("ab(cdefgh)?").as_deref(), Some("ab"));
+        }
+
+        #[test]
+        fn none_for_alternations_and_short_runs() {
+            assert_eq!(required_literal("foo|barbaz"), None);
+            assert_eq!(required_literal(r"\d+x"), None);
+        }
+    }
+
+    mod backend_env {
+        use super::*;
+
+        #[test]
+        fn passes_translatable_operators() {
+            // Act
+            let env = parse_test("ext:pdf,odt in:/srv app:").backend_env();
+
+            // Assert
+            assert_eq!(env, vec![
+                ("LAUNCHER_EXT", "pdf,odt".to_string()),
+                ("LAUNCHER_IN", "/srv".to_string()),
+                ("LAUNCHER_APPS_ONLY", "1".to_string()),
+            ]);
+        }
+
+        #[test]
+        fn empty_without_operators() {
+            assert!(parse_test("notes").backend_env().is_empty());
+        }
+    }
+
+    mod backend_note {
+        use super::*;
+
+        #[test]
+        fn set_for_regex_without_literal() {
+            assert!(parse_test(r"/\d+x/").backend_note().is_some());
+        }
+
+        #[test]
+        fn absent_when_something_narrows_the_index() {
+            assert!(parse_test(r"/report\d+/").backend_note().is_none());
+            assert!(parse_test(r"/\d+x/ ext:png").backend_note().is_none());
+            assert!(parse_test(r"/\d+x/ in:~/Pictures").backend_note().is_none());
+            assert!(parse_test(r"/\d+x/ app:").backend_note().is_none());
+            assert!(parse_test("notes").backend_note().is_none());
+        }
+    }
+
+    mod matches {
+        use super::*;
+
+        #[test]
+        fn filters_by_extension_case_insensitively() {
+            let parsed = parse_test("ext:pdf");
+            assert!(parsed.matches(&result("/a/Invoice.PDF", false), SystemTime::now()));
+            assert!(!parsed.matches(&result("/a/invoice.txt", false), SystemTime::now()));
+        }
+
+        #[test]
+        fn filters_by_kind() {
+            // Arrange
+            let dirs = parse_test("type:dir");
+            let files = parse_test("type:file");
+            let apps = parse_test("app:");
+
+            // Assert
+            assert!(dirs.matches(&result("/a/proj", true), SystemTime::now()));
+            assert!(!dirs.matches(&result("/a/proj.txt", false), SystemTime::now()));
+            assert!(files.matches(&result("/a/proj.txt", false), SystemTime::now()));
+            assert!(!files.matches(&result("/usr/share/applications/x.desktop", false), SystemTime::now()));
+            assert!(apps.matches(&result("/usr/share/applications/x.desktop", false), SystemTime::now()));
+        }
+
+        #[test]
+        fn phrases_and_negations_ignore_accents() {
+            // Arrange
+            let phrase = parse_test("\"cafe menu\"");
+            let negated = parse_test("menu !cafe");
+            let file = SearchResult { name: "Caf\u{e9} Menu.pdf".to_string(), ..result("/a/Caf\u{e9} Menu.pdf", false) };
+
+            // Assert
+            assert!(phrase.matches(&file, SystemTime::now()));
+            assert!(!negated.matches(&file, SystemTime::now()));
+        }
+
+        #[test]
+        fn negation_skips_directories_that_only_share_the_term() {
+            // Arrange
+            let parsed = parse_test("!test");
+            let file = |path: &str| SearchResult { name: path.rsplit('/').next().unwrap().to_string(), ..result(path, false) };
+
+            // Assert
+            assert!(parsed.matches(&file("/home/me/tests/notes.md"), SystemTime::now()));
+            assert!(parsed.matches(&file("/home/me/contest/notes.md"), SystemTime::now()));
+            assert!(!parsed.matches(&file("/home/me/test/notes.md"), SystemTime::now()));
+            assert!(!parsed.matches(&file("/home/me/notes_test.md"), SystemTime::now()));
+        }
+
+        #[test]
+        fn filters_by_location() {
+            let parsed = parse_test("in:~/Documents");
+            assert!(parsed.matches(&result("/home/me/Documents/tax.pdf", false), SystemTime::now()));
+            assert!(!parsed.matches(&result("/home/me/Documents-old/tax.pdf", false), SystemTime::now()));
+        }
+
+        #[test]
+        fn filters_by_size() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("big.bin");
+            fs::write(&path, vec![0u8; 2048]).unwrap();
+            let file = result(&path.to_string_lossy(), false);
+
+            // Assert
+            assert!(parse_test("size:>1K").matches(&file, SystemTime::now()));
+            assert!(!parse_test("size:<1K").matches(&file, SystemTime::now()));
+        }
+
+        #[test]
+        fn filters_by_age() {
+            // Arrange
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("new.txt");
+            fs::write(&path, "x").unwrap();
+            let file = result(&path.to_string_lossy(), false);
+            let in_ten_days = SystemTime::now() + Duration::from_secs(10 * 86_400);
+
+            // Assert
+            assert!(parse_test("modified:<7d").matches(&file, SystemTime::now()));
+            assert!(!parse_test("modified:>7d").matches(&file, SystemTime::now()));
+            assert!(parse_test("modified:>7d").matches(&file, in_ten_days));
+        }
+
+        #[test]
+        fn missing_file_fails_metadata_filters() {
+            assert!(!parse_test("size:>0").matches(&result("/no/such/file", false), SystemTime::now()));
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Include roots, exclude globs and per-root boosts from the `scope` config section.
+//!
+//! These run on the paths every backend returns, so they apply the same way to
+//! plocate, mdfind, Everything and the desktop file search.
+
+use crate::config::ScopeConfig;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// Replaces a leading `~` with `home`.
+pub fn expand_home_in(path: &str, home: &Path) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home.display(), rest),
+        _ => path.to_string(),
+    }
+}
+
+pub fn expand_home(path: &str) -> String {
+    match dirs::home_dir() {
+        Some(home) => expand_home_in(path, &home),
+        None => path.to_string(),
+    }
+}
+
+/// True when `path` is `root` or inside it; `/usr/lib` does not contain `/usr/lib64`.
+pub fn is_under(path: &str, root: &str) -> bool {
+    let root = root.trim_end_matches('/');
+    path.strip_prefix(root).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Turns an exclude pattern into globs over absolute paths. A pattern starting with
+/// `/` or `~` is anchored; anything else matches at any depth, like `.gitignore`.
+/// Matching a directory also excludes everything below it.
+pub fn exclude_globs(pattern: &str, home: &Path) -> Result<Vec<globset::Glob>, globset::Error> {
+    let pattern = expand_home_in(pattern.trim_end_matches('/'), home);
+    let base = if pattern.starts_with('/') { pattern } else { format!("**/{}", pattern) };
+    [base.clone(), format!("{}/**", base)].iter()
+        .map(|glob| GlobBuilder::new(glob).literal_separator(true).build())
+        .collect()
+}
+
+/// Extra score for a path: the boost of the deepest configured root containing it.
+pub fn root_boost(path: &str, scope: &ScopeConfig) -> i32 {
+    scope.boosts.iter()
+        .map(|(root, boost)| (expand_home(root), *boost))
+        .filter(|(root, _)| is_under(path, root))
+        .max_by_key(|(root, _)| root.len())
+        .map_or(0, |(_, boost)| boost)
+}
+
+/// Compiled include and exclude rules.
+pub struct PathFilter {
+    include: Vec<String>,
+    exclude: GlobSet,
+}
+
+impl PathFilter {
+    /// Invalid patterns are skipped here; config validation reports them.
+    pub fn new(scope: &ScopeConfig) -> Self {
+        let home = dirs::home_dir().unwrap_or_default();
+        let mut builder = GlobSetBuilder::new();
+        for glob in scope.exclude.iter().filter_map(|p| exclude_globs(p, &home).ok()).flatten() {
+            builder.add(glob);
+        }
+        Self {
+            include: scope.include.iter().map(|root| expand_home_in(root, &home)).collect(),
+            exclude: builder.build().unwrap_or_else(|_| GlobSet::empty()),
+        }
+    }
+
+    /// Applications are always in scope; other paths must be under an include root
+    /// (when any are set) and match no exclude pattern.
+    pub fn allows(&self, path: &str) -> bool {
+        if self.exclude.is_match(path) {
+            return false;
+        }
+        path.ends_with(".desktop") || self.include.is_empty() || self.include.iter().any(|root| is_under(path, root))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn scope(include: &[&str], exclude: &[&str]) -> ScopeConfig {
+        ScopeConfig {
+            include: include.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+            boosts: BTreeMap::new(),
+            ..ScopeConfig::default()
+        }
+    }
+
+    mod expand_home_in {
+        use super::*;
+
+        #[test]
+        fn expands_leading_tilde() {
+            assert_eq!(expand_home_in("~/code", Path::new("/home/me")), "/home/me/code");
+            assert_eq!(expand_home_in("~", Path::new("/home/me")), "/home/me");
+        }
+
+        #[test]
+        fn leaves_other_paths_alone() {
+            assert_eq!(expand_home_in("~other/code", Path::new("/home/me")), "~other/code");
+            assert_eq!(expand_home_in("/opt", Path::new("/home/me")), "/opt");
+        }
+    }
+
+    mod is_under {
+        use super::*;
+
+        #[test]
+        fn matches_root_and_children() {
+            assert!(is_under("/usr/lib", "/usr/lib"));
+            assert!(is_under("/usr/lib/x/y", "/usr/lib/"));
+        }
+
+        #[test]
+        fn respects_component_boundaries() {
+            assert!(!is_under("/usr/lib64/x", "/usr/lib"));
+        }
+    }
+
+    mod path_filter {
+        use super::*;
+
+        #[test]
+        fn unanchored_patterns_match_any_depth() {
+            // Arrange
+            let filter = PathFilter::new(&scope(&[], &["node_modules", "target"]));
+
+            // Assert
+            assert!(!filter.allows("/home/me/app/node_modules/react/index.js"));
+            assert!(!filter.allows("/home/me/app/target"));
+            assert!(filter.allows("/home/me/app/targets.md"));
+        }
+
+        #[test]
+        fn anchored_patterns_match_from_root() {
+            // Arrange
+            let filter = PathFilter::new(&scope(&[], &["/mnt"]));
+
+            // Assert
+            assert!(!filter.allows("/mnt/backup/notes.md"));
+            assert!(filter.allows("/home/me/mnt/notes.md"));
+        }
+
+        #[test]
+        fn wildcards_stay_within_one_component() {
+            // Arrange
+            let filter = PathFilter::new(&scope(&[], &["build*out"]));
+
+            // Assert
+            assert!(!filter.allows("/repo/build-out/app.js"));
+            assert!(filter.allows("/repo/build/out/app.js"));
+        }
+
+        #[test]
+        fn include_roots_limit_files_but_not_apps() {
+            // Arrange
+            let filter = PathFilter::new(&scope(&["/home/me/code"], &[]));
+
+            // Assert
+            assert!(filter.allows("/home/me/code/main.rs"));
+            assert!(!filter.allows("/home/me/Downloads/main.rs"));
+            assert!(filter.allows("/usr/share/applications/firefox.desktop"));
+        }
+
+        #[test]
+        fn exclude_wins_over_include() {
+            let filter = PathFilter::new(&scope(&["/home/me/code"], &[".git"]));
+            assert!(!filter.allows("/home/me/code/.git/config"));
+        }
+
+        #[test]
+        fn default_scope_drops_build_outputs() {
+            let filter = PathFilter::new(&ScopeConfig::default());
+            assert!(!filter.allows("/repo/crates/core/target/debug/build.rs"));
+            assert!(!filter.allows("/timeshift/snapshots/x"));
+            assert!(filter.allows("/repo/crates/core/src/lib.rs"));
+        }
+    }
+
+    mod root_boost {
+        use super::*;
+
+        #[test]
+        fn deepest_root_wins() {
+            // Arrange
+            let mut scope = scope(&[], &[]);
+            scope.boosts.insert("/work".to_string(), 100);
+            scope.boosts.insert("/work/main".to_string(), 300);
+
+            // Act & Assert
+            assert_eq!(root_boost("/work/main/src/lib.rs", &scope), 300);
+            assert_eq!(root_boost("/work/other/lib.rs", &scope), 100);
+            assert_eq!(root_boost("/elsewhere/lib.rs", &scope), 0);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! `launcher --tune`: fits ranking weights to the selection history.
+//!
+//! Every recorded selection is ranked again with trial weights. Coordinate descent
+//! tries scaling and nudging one weight at a time and keeps a change when the chosen
+//! results move up on average, measured as mean reciprocal rank.
+
+use crate::config::{Config, GitIgnored, WEIGHTS};
+use crate::history::Selection;
+use crate::{frequency_bonus, query, score_terms, SearchResult};
+
+/// Fewer selections than this say more about chance than about preferences.
+pub const MIN_SELECTIONS: usize = 10;
+const MAX_ROUNDS: usize = 6;
+const FACTORS: [f64; 4] = [0.0, 0.5, 1.5, 2.0];
+/// Added to and taken from each weight, so weights at 0 can still move.
+const STEP: i32 = 25;
+
+/// 1-based rank of the chosen result when `selection` is replayed with `config`.
+/// Ties count against the chosen result, so flattening every weight cannot win.
+/// Only what was recorded with the pick is used, never the file system.
+fn rank(selection: &Selection, config: &Config) -> Option<usize> {
+    let (_, text) = config.for_query(&selection.query);
+    let text = query::parse(text).text;
+    let penalize_ignored = config.scope.gitignored == GitIgnored::Penalize;
+    let scores: Vec<(bool, i32)> = selection.candidates.iter()
+        .map(|c| {
+            let result = SearchResult { path: c.path.clone(), name: c.name.clone(), is_dir: c.is_dir, icon: None, score: None };
+            let score = score_terms(&result, &text, frequency_bonus(c.uses, config), penalize_ignored && c.ignored, config);
+            (c.path == selection.chosen, score.total)
+        })
+        .collect();
+    let chosen = scores.iter().find(|(chosen, _)| *chosen)?.1;
+    Some(1 + scores.iter().filter(|(is_chosen, score)| !is_chosen && *score <= chosen).count())
+}
+
+/// Mean of 1/rank over the selections: 1.0 when every pick would rank first.
+pub fn mean_reciprocal_rank(history: &[Selection], config: &Config) -> f64 {
+    let ranks: Vec<usize> = history.iter().filter_map(|s| rank(s, config)).collect();
+    if ranks.is_empty() {
+        return 0.0;
+    }
+    ranks.iter().map(|&r| 1.0 / r as f64).sum::<f64>() / ranks.len() as f64
+}
+
+/// Configs differing from `config` in one weight, each kept within what the config accepts.
+fn neighbours(config: &Config) -> Vec<Config> {
+    let mut out = Vec::new();
+    for (_, weight, minimum) in WEIGHTS {
+        let value = *weight(&mut config.clone());
+        let mut values: Vec<i32> = FACTORS.iter().map(|f| (value as f64 * f).round() as i32).collect();
+        values.extend([value.saturating_add(STEP), value.saturating_sub(STEP)]);
+        values.iter_mut().for_each(|v| *v = (*v).max(minimum));
+        values.sort_unstable();
+        values.dedup();
+        for v in values.into_iter().filter(|&v| v != value) {
+            let mut next = config.clone();
+            *weight(&mut next) = v;
+            out.push(next);
+        }
+    }
+    for flip in [|c: &mut Config| c.prefer_apps = !c.prefer_apps, |c: &mut Config| c.penalize_hidden = !c.penalize_hidden] {
+        let mut next = config.clone();
+        flip(&mut next);
+        out.push(next);
+    }
+    out
+}
+
+pub struct Tuned {
+    pub config: Config,
+    pub before: f64,
+    pub after: f64,
+}
+
+/// Starts from `base` and keeps every single-weight change that raises the score.
+pub fn fit(history: &[Selection], base: &Config) -> Tuned {
+    let before = mean_reciprocal_rank(history, base);
+    let (mut best, mut best_score) = (base.clone(), before);
+    for _ in 0..MAX_ROUNDS {
+        let mut improved = false;
+        for candidate in neighbours(&best) {
+            let score = mean_reciprocal_rank(history, &candidate);
+            if score > best_score + f64::EPSILON {
+                (best, best_score) = (candidate, score);
+                improved = true;
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    Tuned { config: best, before, after: best_score }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Candidate;
+
+    fn candidate(path: &str, uses: f64) -> Candidate {
+        let name = path.rsplit('/').next().unwrap().to_string();
+        Candidate { path: path.to_string(), name, is_dir: false, uses, ignored: false }
+    }
+
+    /// "notes" typed, the often-used deep file picked over a shallow exact match.
+    fn picks_frequent_file() -> Selection {
+        Selection {
+            query: "notes".to_string(),
+            chosen: "/w/a/b/c/notes-2024.md".to_string(),
+            candidates: vec![candidate("/w/notes", 0.0), candidate("/w/a/b/c/notes-2024.md", 1.0)],
+            time: 0,
+        }
+    }
+
+    mod rank {
+        use super::*;
+
+        #[test]
+        fn ties_count_against_the_choice() {
+            // Arrange
+            let selection = Selection {
+                query: "x".to_string(),
+                chosen: "/a/x".to_string(),
+                candidates: vec![candidate("/a/x", 0.0), candidate("/b/x", 0.0)],
+                time: 0,
+            };
+
+            // Assert
+            assert_eq!(rank(&selection, &Config::default()), Some(2));
+        }
+
+        #[test]
+        fn uses_recorded_ignore_state() {
+            // Arrange
+            let selection = Selection {
+                query: "x".to_string(),
+                chosen: "/a/x".to_string(),
+                candidates: vec![Candidate { ignored: true, ..candidate("/b/x", 0.0) }, candidate("/a/x", 0.0)],
+                time: 0,
+            };
+
+            // Assert
+            assert_eq!(rank(&selection, &Config::default()), Some(1));
+        }
+
+        #[test]
+        fn missing_choice_is_skipped() {
+            let selection = Selection { chosen: "/elsewhere".to_string(), ..picks_frequent_file() };
+            assert_eq!(rank(&selection, &Config::default()), None);
+        }
+    }
+
+    mod neighbours {
+        use super::*;
+
+        #[test]
+        fn explores_negative_exact_bonus() {
+            // Act
+            let configs = neighbours(&Config::default());
+
+            // Assert
+            assert!(configs.iter().any(|c| c.exact_bonus == -STEP));
+        }
+
+        #[test]
+        fn keeps_penalties_non_negative() {
+            // Arrange
+            let base = Config { depth_penalty: 10, ..Config::default() };
+
+            // Act
+            let configs = neighbours(&base);
+
+            // Assert
+            assert!(configs.iter().all(|c| c.depth_penalty >= 0 && c.frequency_bonus >= 0));
+            assert!(configs.iter().any(|c| c.depth_penalty == 0));
+        }
+    }
+
+    mod fit {
+        use super::*;
+
+        #[test]
+        fn moves_chosen_results_up() {
+            // Arrange
+            let history = vec![picks_frequent_file(); MIN_SELECTIONS];
+            let base = Config { frequency_bonus: 100, ..Config::default() };
+
+            // Act
+            let tuned = fit(&history, &base);
+
+            // Assert
+            assert_eq!(tuned.before, 0.5);
+            assert_eq!(tuned.after, 1.0);
+            assert_eq!(rank(&history[0], &tuned.config), Some(1));
+        }
+
+        #[test]
+        fn keeps_weights_that_already_rank_first() {
+            // Arrange
+            let history = vec![picks_frequent_file(); MIN_SELECTIONS];
+
+            // Act
+            let tuned = fit(&history, &Config::default());
+
+            // Assert
+            assert_eq!(tuned.before, 1.0);
+            assert_eq!(tuned.config, Config::default());
+        }
+    }
+}
@@ -65,6 +65,18 @@ run_test "path with spaces"
 result=$("$BACKEND" "my documents" 2>/dev/null) || true
 pass "path with spaces handled (got $(echo "$result" | wc -l) results)"
 
+run_test "each word is a separate plocate pattern"
+stub_dir=$(mktemp -d)
+printf '#!/bin/bash\nprintf "%%s\\n" "$*"\n' > "$stub_dir/plocate"
+chmod +x "$stub_dir/plocate"
+result=$(PATH="$stub_dir:$PATH" LAUNCHER_EXT=pdf "$BACKEND" "report 2024" 2>/dev/null) || true
+[[ "$result" == *"*report* *2024* *.pdf"* ]] && pass "words and extension passed as separate patterns" || fail "got: $result"
+
+run_test "accented words are also searched with wildcards"
+result=$(PATH="$stub_dir:$PATH" "$BACKEND" "café" 2>/dev/null) || true
+[[ "$result" == *"*café*"* && "$result" == *"*caf**"* ]] && pass "accented and wildcard patterns searched" || fail "got: $result"
+rm -rf "$stub_dir"
+
 echo ""
 echo "=== Summary ==="
 echo "Passed: $PASS"
@@ -1,15 +1,15 @@
 const PLUGIN_ID = window.location.pathname.split('/')[2];
 const CONFIG_URL = `/api/plugins/${PLUGIN_ID}/config`;
+// Generated by `launcher --print-config-schema`; defaults and limits come from here.
+const SCHEMA_URL = 'config.schema.json';
 
-const DEFAULTS = {
-    half_life_days: 7,
-    frequency_bonus: 500,
-    prefer_apps: true,
-    penalize_hidden: true,
-    depth_penalty: 2,
-    exact_bonus: 0,
-    prefix_penalty: 100,
-    contains_penalty: 200
+const NUMBER_INPUTS = {
+    half_life_days: 'halfLife',
+    frequency_bonus: 'frequencyBonus',
+    depth_penalty: 'depthPenalty',
+    exact_bonus: 'exactBonus',
+    prefix_penalty: 'prefixPenalty',
+    contains_penalty: 'containsPenalty'
 };
 
 const elements = {
@@ -17,28 +17,62 @@ const elements = {
     frequencyBonus: document.getElementById('frequency-bonus'),
     preferApps: document.getElementById('prefer-apps'),
     penalizeHidden: document.getElementById('penalize-hidden'),
+    transliterate: document.getElementById('transliterate'),
     depthPenalty: document.getElementById('depth-penalty'),
     exactBonus: document.getElementById('exact-bonus'),
     prefixPenalty: document.getElementById('prefix-penalty'),
     containsPenalty: document.getElementById('contains-penalty'),
+    profile: document.getElementById('profile'),
+    terminalCommand: document.getElementById('terminal-command'),
+    scopeInclude: document.getElementById('scope-include'),
+    scopeExclude: document.getElementById('scope-exclude'),
+    scopeBoosts: document.getElementById('scope-boosts'),
+    scopeGitignored: document.getElementById('scope-gitignored'),
+    configWarnings: document.getElementById('config-warnings'),
     saveBtn: document.getElementById('save-btn'),
     resetBtn: document.getElementById('reset-btn'),
     saveStatus: document.getElementById('save-status')
 };
 
-let config = { ...DEFAULTS };
+let properties = {};
+let defaults = {};
+let config = {};
+
+async function loadSchema() {
+    const response = await fetch(SCHEMA_URL);
+    properties = (await response.json()).properties;
+    defaults = Object.fromEntries(
+        Object.entries(properties)
+            .filter(([, property]) => 'default' in property)
+            .map(([key, property]) => [key, property.default])
+    );
+}
 
 async function loadConfig() {
+    await loadSchema();
+    config = { ...defaults };
     try {
         const response = await fetch(CONFIG_URL);
         if (response.ok) {
             const loaded = await response.json();
-            config = { ...DEFAULTS, ...loaded };
+            config = {
+                ...defaults,
+                ...loaded,
+                terminal: { ...defaults.terminal, ...loaded.terminal },
+                scope: { ...defaults.scope, ...loaded.scope }
+            };
+            showUnknownKeys(Object.keys(loaded).filter(key => !(key in properties)));
         }
     } catch (e) {
         console.warn('Could not load config, using defaults');
     }
     applyConfigToUI();
+    validate(config);
+}
+
+function showUnknownKeys(keys) {
+    elements.configWarnings.hidden = keys.length === 0;
+    elements.configWarnings.textContent = `Unknown keys in config.json are ignored: ${keys.join(', ')}`;
 }
 
 function applyConfigToUI() {
@@ -46,27 +80,105 @@ function applyConfigToUI() {
     elements.frequencyBonus.value = config.frequency_bonus;
     elements.preferApps.checked = config.prefer_apps;
     elements.penalizeHidden.checked = config.penalize_hidden;
+    elements.transliterate.checked = config.transliterate;
     elements.depthPenalty.value = config.depth_penalty;
     elements.exactBonus.value = config.exact_bonus;
     elements.prefixPenalty.value = config.prefix_penalty;
     elements.containsPenalty.value = config.contains_penalty;
+    elements.profile.replaceChildren(
+        ...['', ...Object.keys(config.profiles)].map(name => new Option(name || 'None', name))
+    );
+    elements.profile.value = config.profile;
+    elements.terminalCommand.value = config.terminal.command;
+    elements.scopeInclude.value = config.scope.include.join('\n');
+    elements.scopeExclude.value = config.scope.exclude.join('\n');
+    elements.scopeBoosts.value = Object.entries(config.scope.boosts)
+        .map(([root, boost]) => `${root} = ${boost}`)
+        .join('\n');
+    elements.scopeGitignored.value = config.scope.gitignored;
+}
+
+function lines(textarea) {
+    return textarea.value.split('\n').map(line => line.trim()).filter(Boolean);
+}
+
+// `folder = bonus` lines; a line without a whole-number bonus becomes NaN and fails validation.
+function parseBoosts(textarea) {
+    return Object.fromEntries(lines(textarea).map(line => {
+        const at = line.lastIndexOf('=');
+        const bonus = line.slice(at + 1).trim();
+        return at < 0 ? [line, NaN] : [line.slice(0, at).trim(), /^-?\d+$/.test(bonus) ? Number(bonus) : NaN];
+    }));
 }
 
 function collectConfigFromUI() {
     return {
-        half_life_days: parseInt(elements.halfLife.value, 10) || DEFAULTS.half_life_days,
-        frequency_bonus: parseInt(elements.frequencyBonus.value, 10) || 0,
+        ...config,
+        half_life_days: Number(elements.halfLife.value),
+        frequency_bonus: Number(elements.frequencyBonus.value),
         prefer_apps: elements.preferApps.checked,
         penalize_hidden: elements.penalizeHidden.checked,
-        depth_penalty: parseInt(elements.depthPenalty.value, 10) || 0,
-        exact_bonus: parseInt(elements.exactBonus.value, 10) || 0,
-        prefix_penalty: parseInt(elements.prefixPenalty.value, 10) || 0,
-        contains_penalty: parseInt(elements.containsPenalty.value, 10) || 0
+        transliterate: elements.transliterate.checked,
+        depth_penalty: Number(elements.depthPenalty.value),
+        exact_bonus: Number(elements.exactBonus.value),
+        prefix_penalty: Number(elements.prefixPenalty.value),
+        contains_penalty: Number(elements.containsPenalty.value),
+        profile: elements.profile.value,
+        terminal: { ...config.terminal, command: elements.terminalCommand.value.trim() },
+        scope: {
+            include: lines(elements.scopeInclude),
+            exclude: lines(elements.scopeExclude),
+            boosts: parseBoosts(elements.scopeBoosts),
+            gitignored: elements.scopeGitignored.value
+        }
     };
 }
 
+function fieldError(input) {
+    let error = input.parentElement.querySelector('.field-error');
+    if (!error) {
+        error = document.createElement('span');
+        error.className = 'field-error';
+        input.after(error);
+    }
+    return error;
+}
+
+function numberError(input, value, property) {
+    if (input.value.trim() === '' || !Number.isFinite(value)) return 'Must be a number';
+    if (property.type === 'integer' && !Number.isInteger(value)) return 'Must be a whole number';
+    if ('exclusiveMinimum' in property && value <= property.exclusiveMinimum) {
+        return `Must be greater than ${property.exclusiveMinimum}`;
+    }
+    if ('minimum' in property && value < property.minimum) return `Must be ${property.minimum} or more`;
+    return '';
+}
+
+// Marks each invalid field against the schema and returns whether the whole config is valid.
+function validate(candidate) {
+    let valid = true;
+    for (const [key, inputName] of Object.entries(NUMBER_INPUTS)) {
+        const input = elements[inputName];
+        const message = numberError(input, candidate[key], properties[key] || {});
+        input.classList.toggle('invalid', message !== '');
+        fieldError(input).textContent = message;
+        valid = valid && message === '';
+    }
+    const badBoosts = Object.entries(candidate.scope.boosts).filter(([, boost]) => !Number.isInteger(boost));
+    const boostMessage = badBoosts.length ? `Needs a whole-number bonus: ${badBoosts.map(([root]) => root).join(', ')}` : '';
+    elements.scopeBoosts.classList.toggle('invalid', boostMessage !== '');
+    fieldError(elements.scopeBoosts).textContent = boostMessage;
+    return valid && boostMessage === '';
+}
+
 async function saveConfig() {
     const newConfig = collectConfigFromUI();
+    if (!validate(newConfig)) {
+        elements.saveStatus.textContent = 'Fix the highlighted fields first';
+        elements.saveStatus.style.color = '#ff6b6b';
+        return;
+    }
+    elements.saveStatus.style.color = '';
 
     elements.saveBtn.disabled = true;
     elements.saveStatus.textContent = 'Saving...';
@@ -96,12 +208,16 @@ async function saveConfig() {
 }
 
 function resetToDefaults() {
-    config = { ...DEFAULTS };
+    config = { ...defaults };
     applyConfigToUI();
+    validate(config);
 }
 
 elements.saveBtn.addEventListener('click', saveConfig);
 elements.resetBtn.addEventListener('click', resetToDefaults);
+for (const inputName of [...Object.values(NUMBER_INPUTS), 'scopeBoosts']) {
+    elements[inputName].addEventListener('input', () => validate(collectConfigFromUI()));
+}
 
 document.addEventListener('keydown', (e) => {
     if (e.key === 's' && (e.ctrlKey || e.metaKey)) {
@@ -1,23 +1,97 @@
 let results = [];
 let selectedIndex = 0;
 let debounceTimer = null;
+let customActions = [];
+let panel = null;
+let toastTimer = null;
+let marked = new Set();
+let searchMode = 'all';
+let profiles = [];
+let explain = false;
+// Why the last search could not look in the file index, when it could not.
+let searchNote = null;
+
+const MODE_PLACEHOLDERS = {
+    all: 'Search files...',
+    apps: 'Search applications...',
+    files: 'Search files only...'
+};
+
+const dmenu = window.DMENU || null;
+
+function searchPlaceholder() {
+    return dmenu ? (dmenu.prompt || 'Filter...') : MODE_PLACEHOLDERS[searchMode];
+}
+
+const NAME_PROMPTS = {
+    'rename': { title: 'Rename', placeholder: 'New name' },
+    'new-file': { title: 'New file next to', placeholder: 'File name' },
+    'new-folder': { title: 'New folder next to', placeholder: 'Folder name' }
+};
+const FILE_OPS = ['trash', 'duplicate'];
+
+// Mirrors the operators parsed in src/query.rs.
+const OPERATORS = [
+    { name: 'ext', example: 'ext:pdf', help: 'File extension, comma-separated' },
+    { name: 'type', example: 'type:dir', help: 'file, dir or app' },
+    { name: 'in', example: 'in:~/Documents', help: 'Inside a folder' },
+    { name: 'app', example: 'app:term', help: 'Applications only' },
+    { name: 'modified', example: 'modified:<7d', help: 'Changed within (<) or before (>); m, h, d, w, y' },
+    { name: 'size', example: 'size:>100M', help: 'Larger (>) or smaller (<); K, M, G' }
+];
+const MATCH_MODES = ['"exact phrase"', '/regex/', '!exclude'];
 
 const searchInput = document.getElementById('search');
 const resultsContainer = document.getElementById('results');
 const actionHint = document.getElementById('action-hint');
+const toast = document.getElementById('toast');
+const configIssues = document.getElementById('config-issues');
+const operatorHints = document.getElementById('operator-hints');
+
+function renderDmenuResults() {
+    if (results.length === 0) {
+        resultsContainer.innerHTML = `<div class="empty-state">No matches</div>`;
+        return;
+    }
+    resultsContainer.innerHTML = results.map((result, index) => `
+        <div class="result-item dmenu-item ${index === selectedIndex ? 'selected' : ''} ${marked.has(result.name) ? 'marked' : ''}" data-index="${index}">
+            <div class="result-name">${escapeHtml(result.name)}</div>
+        </div>
+    `).join('');
+
+    const selected = resultsContainer.querySelector('.selected');
+    if (selected) {
+        selected.scrollIntoView({ block: 'nearest' });
+    }
+}
 
 function renderResults() {
+    if (dmenu) {
+        renderDmenuResults();
+        return;
+    }
     if (results.length === 0) {
         resultsContainer.innerHTML = `
             <div class="empty-state">
-                <div>Type to search files and directories</div>
+                <div>${searchNote ? escapeHtml(searchNote) : 'Type to search files and directories'}</div>
                 <div class="keyboard-hints">
                     <span><kbd>Enter</kbd> Open</span>
                     <span><kbd>Ctrl+Enter</kbd> Terminal</span>
                     <span><kbd>Shift+Enter</kbd> Folder</span>
                     <span><kbd>Alt+Enter</kbd> Copy</span>
+                    <span><kbd>Tab</kbd> Actions</span>
+                    ${profiles.length ? '<span><kbd>Ctrl+P</kbd> Ranking profile</span>' : ''}
+                    <span><kbd>Ctrl+E</kbd> ${explain ? 'Hide' : 'Show'} scores</span>
+                    ${customActions.filter(a => a.key).map(a =>
+                        `<span><kbd>${escapeHtml(a.key)}</kbd> ${escapeHtml(a.name)}</span>`
+                    ).join('')}
                     <span><kbd>Esc</kbd> Close</span>
                 </div>
+                <div class="keyboard-hints">
+                    ${[...OPERATORS.map(op => op.example), ...MATCH_MODES].map(example =>
+                        `<code>${escapeHtml(example)}</code>`
+                    ).join('')}
+                </div>
             </div>
         `;
         return;
@@ -32,6 +106,7 @@ function renderResults() {
             <div class="result-info">
                 <div class="result-name">${escapeHtml(result.name)}</div>
                 <div class="result-path">${escapeHtml(result.path)}</div>
+                ${explain && result.score ? scoreBreakdown(result.score) : ''}
             </div>
         </div>
     `).join('');
@@ -42,6 +117,147 @@ function renderResults() {
     }
 }
 
+// Mirrors ScoreBreakdown in src/main.rs; lower totals rank higher.
+const SCORE_TERMS = [
+    ['match_penalty', 'match', 1],
+    ['type_penalty', 'type', 1],
+    ['path_penalty', 'path', 1],
+    ['length_penalty', 'length', 1],
+    ['frequency_bonus', 'frequency', -1]
+];
+
+function scoreBreakdown(score) {
+    const terms = SCORE_TERMS.map(([key, label, sign]) =>
+        `<span>${label} ${sign < 0 ? '−' : '+'}${score[key]}</span>`
+    ).join('');
+    return `<div class="result-score"><strong>${score.total}</strong>${terms}</div>`;
+}
+
+function renderPanel() {
+    if (panel.mode === 'input') {
+        const prompt = NAME_PROMPTS[panel.op];
+        resultsContainer.innerHTML = `
+            <div class="panel-header">
+                <span>${prompt.title}</span> <strong>${escapeHtml(panel.result.name)}</strong>
+                <span class="panel-hint"><kbd>Enter</kbd> Confirm <kbd>Esc</kbd> Back</span>
+            </div>
+        `;
+        return;
+    }
+    const header = panel.mode === 'apps'
+        ? `<div class="panel-header">
+                <span>Open</span> <strong>${escapeHtml(panel.result.name)}</strong> <span>with</span>
+                <span class="panel-hint"><kbd>Ctrl+Enter</kbd> Open and set as default</span>
+           </div>`
+        : `<div class="panel-header">
+                <span>Actions for</span> <strong>${escapeHtml(panel.result.name)}</strong>
+           </div>`;
+    const items = panel.filtered.map((action, index) => `
+        <div class="result-item action-item ${index === panel.index ? 'selected' : ''}" data-index="${index}">
+            ${action.icon ? `<img class="result-icon" src="${action.icon}">` : ''}
+            <div class="result-name">${escapeHtml(action.label)}</div>
+            ${action.key ? `<kbd>${escapeHtml(action.key)}</kbd>` : ''}
+        </div>
+    `).join('');
+    const empty = panel.mode === 'apps' ? 'No applications found' : 'No matching actions';
+    resultsContainer.innerHTML = header + (items || `<div class="empty-state">${empty}</div>`);
+
+    const selected = resultsContainer.querySelector('.selected');
+    if (selected) {
+        selected.scrollIntoView({ block: 'nearest' });
+    }
+}
+
+function openActionPanel() {
+    if (results.length === 0) return;
+    panel = {
+        mode: 'actions',
+        result: results[selectedIndex],
+        actionList: [],
+        actions: [],
+        filtered: [],
+        index: 0,
+        savedQuery: searchInput.value
+    };
+    searchInput.value = '';
+    searchInput.placeholder = 'Filter actions...';
+    renderPanel();
+    window.ipc.postMessage(JSON.stringify({ type: 'list_actions', path: panel.result.path }));
+}
+
+function closeActionPanel() {
+    searchInput.value = panel.savedQuery;
+    searchInput.placeholder = searchPlaceholder();
+    panel = null;
+    renderResults();
+}
+
+function filterPanel(text) {
+    const needle = text.toLowerCase();
+    panel.filtered = panel.actions.filter(a => a.label.toLowerCase().includes(needle));
+    panel.index = 0;
+    renderPanel();
+}
+
+window.onActions = function(actions) {
+    if (!panel) return;
+    panel.actionList = actions;
+    if (panel.mode !== 'actions') return;
+    panel.actions = actions;
+    filterPanel(searchInput.value.trim());
+};
+
+function showApps() {
+    panel.mode = 'apps';
+    panel.actions = [];
+    panel.filtered = [];
+    searchInput.value = '';
+    searchInput.placeholder = 'Filter applications...';
+    renderPanel();
+    window.ipc.postMessage(JSON.stringify({ type: 'list_apps', path: panel.result.path }));
+}
+
+function promptName(op) {
+    panel.mode = 'input';
+    panel.op = op;
+    const prompt = NAME_PROMPTS[op];
+    searchInput.value = op === 'rename' ? panel.result.name : '';
+    searchInput.placeholder = prompt.placeholder;
+    renderPanel();
+    if (op === 'rename') {
+        const dot = panel.result.name.lastIndexOf('.');
+        searchInput.setSelectionRange(0, dot > 0 ? dot : panel.result.name.length);
+    }
+}
+
+function runFileOp(op, name = '') {
+    window.ipc.postMessage(JSON.stringify({
+        type: 'file_op',
+        path: panel.result.path,
+        op: op,
+        name: name
+    }));
+}
+
+function backToActions() {
+    panel.mode = 'actions';
+    panel.actions = panel.actionList;
+    searchInput.value = '';
+    searchInput.placeholder = 'Filter actions...';
+    filterPanel('');
+}
+
+window.onApps = function(apps) {
+    if (!panel || panel.mode !== 'apps') return;
+    panel.actions = apps.map(app => ({
+        id: app.id,
+        label: app.name,
+        key: app.is_default ? 'Default' : '',
+        icon: app.icon
+    }));
+    filterPanel(searchInput.value.trim());
+};
+
 function escapeHtml(text) {
     const div = document.createElement('div');
     div.textContent = text;
@@ -49,36 +265,188 @@ function escapeHtml(text) {
 }
 
 function search(query) {
-    if (!query) {
+    if (!query && !dmenu) {
         results = [];
         renderResults();
         return;
     }
-    window.ipc.postMessage(JSON.stringify({ type: 'search', query }));
+    window.ipc.postMessage(JSON.stringify({ type: 'search', query, mode: searchMode }));
+}
+
+function currentWord() {
+    const before = searchInput.value.slice(0, searchInput.selectionStart);
+    return before.slice(before.search(/\S*$/)).toLowerCase();
+}
+
+// Operators the word at the cursor could become, or the one whose value is being typed.
+function operatorSuggestions() {
+    const word = currentWord();
+    if (dmenu || panel || word.length < 2) return [];
+    const colon = word.indexOf(':');
+    if (colon < 0) return OPERATORS.filter(op => op.name.startsWith(word));
+    return OPERATORS.filter(op => op.name === word.slice(0, colon));
+}
+
+function renderOperatorHints() {
+    const suggestions = operatorSuggestions();
+    operatorHints.classList.toggle('visible', suggestions.length > 0);
+    operatorHints.innerHTML = suggestions.map(op =>
+        `<span><code>${escapeHtml(op.example)}</code> ${escapeHtml(op.help)}</span>`
+    ).join('') + (completion(suggestions) ? '<span class="operator-complete"><kbd>Shift+Tab</kbd> complete</span>' : '');
 }
 
-window.onSearchResults = function(data) {
+function completion(suggestions) {
+    const word = currentWord();
+    return suggestions.length === 1 && !word.includes(':') ? suggestions[0] : null;
+}
+
+function completeOperator() {
+    const op = completion(operatorSuggestions());
+    if (!op) return false;
+    const end = searchInput.selectionStart;
+    searchInput.setRangeText(`${op.name}:`, end - currentWord().length, end, 'end');
+    renderOperatorHints();
+    return true;
+}
+
+window.setCustomActions = function(actions) {
+    customActions = actions;
+    renderResults();
+};
+
+window.setConfigIssues = function(issues) {
+    configIssues.classList.toggle('visible', issues.length > 0);
+    const hasErrors = issues.some(i => i.severity === 'error');
+    configIssues.innerHTML = `
+        <div class="config-issues-title">${hasErrors ? 'Problems in config.json (defaults used)' : 'Warnings in config.json'}</div>
+        ${issues.map(i => `
+            <div class="config-issue ${i.severity}">${i.field ? `<code>${escapeHtml(i.field)}</code> ` : ''}${escapeHtml(i.message)}</div>
+        `).join('')}
+    `;
+};
+
+window.setProfiles = function(names) {
+    profiles = names;
+    renderResults();
+};
+
+// Swaps the query's `@profile` prefix for the next profile, then for none.
+function cycleProfile() {
+    const match = searchInput.value.match(/^@(\S+)\s*/);
+    const current = match ? profiles.indexOf(match[1]) : -1;
+    if (match && current < 0) return;
+    const rest = match ? searchInput.value.slice(match[0].length) : searchInput.value;
+    const next = profiles[current + 1];
+    searchInput.value = next ? `@${next} ${rest}` : rest;
+    search(searchInput.value.trim());
+}
+
+function parseBinding(binding) {
+    const parts = binding.split('+').map(p => p.trim().toLowerCase());
+    const key = parts.pop();
+    return {
+        key: key === 'return' ? 'enter' : key,
+        ctrl: parts.includes('ctrl'),
+        shift: parts.includes('shift'),
+        alt: parts.includes('alt'),
+        meta: parts.includes('super') || parts.includes('meta')
+    };
+}
+
+function modifiersMatch(binding, e) {
+    return binding.ctrl === e.ctrlKey && binding.shift === e.shiftKey
+        && binding.alt === e.altKey && binding.meta === e.metaKey;
+}
+
+function findCustomAction(e, key = e.key.toLowerCase()) {
+    return customActions.find(a => {
+        if (!a.key) return false;
+        const binding = parseBinding(a.key);
+        return binding.key === key && modifiersMatch(binding, e);
+    });
+}
+
+window.onSearchResults = function(data, note = null) {
     results = data;
+    searchNote = note;
     selectedIndex = 0;
-    renderResults();
+    if (!panel) renderResults();
 };
 
-function executeSelected(action) {
-    if (results.length === 0) return;
-    const selected = results[selectedIndex];
+window.resetLauncher = function() {
+    panel = null;
+    searchMode = 'all';
+    toast.classList.remove('visible');
+    searchInput.value = '';
+    searchInput.placeholder = searchPlaceholder();
+    renderOperatorHints();
+    window.onSearchResults([]);
+};
+
+window.setQuery = function(query, mode = 'all') {
+    window.resetLauncher();
+    searchMode = mode;
+    searchInput.placeholder = searchPlaceholder();
+    searchInput.value = query;
+    search(query.trim());
+};
+
+function executeAction(path, action) {
     window.ipc.postMessage(JSON.stringify({
         type: 'execute',
-        path: selected.path,
+        path: path,
         action: action
     }));
 }
 
+function executeSelected(action) {
+    if (results.length === 0) return;
+    executeAction(results[selectedIndex].path, action);
+}
+
+window.onActionError = function(error) {
+    toast.textContent = `${error.action} failed: ${error.message}`;
+    toast.classList.add('visible');
+    clearTimeout(toastTimer);
+    toastTimer = setTimeout(() => toast.classList.remove('visible'), 4000);
+};
+
+function executePanelAction(setDefault = false) {
+    if (panel.mode === 'input') {
+        const name = searchInput.value.trim();
+        if (name) runFileOp(panel.op, name);
+        return;
+    }
+    const action = panel.filtered[panel.index];
+    if (!action) return;
+    if (panel.mode === 'apps') {
+        window.ipc.postMessage(JSON.stringify({
+            type: 'open_with',
+            path: panel.result.path,
+            app: action.id,
+            set_default: setDefault
+        }));
+    } else if (action.id === 'open-with') {
+        showApps();
+    } else if (NAME_PROMPTS[action.id]) {
+        promptName(action.id);
+    } else if (FILE_OPS.includes(action.id)) {
+        runFileOp(action.id);
+    } else {
+        executeAction(panel.result.path, action.id);
+    }
+}
+
 function close() {
     window.ipc.postMessage(JSON.stringify({ type: 'close' }));
 }
 
 function updateActionHint(e) {
-    if (e.ctrlKey) {
+    if (dmenu) return;
+    const custom = findCustomAction(e, 'enter');
+    if (custom) {
+        actionHint.textContent = custom.name;
+    } else if (e.ctrlKey) {
         actionHint.textContent = 'Terminal';
     } else if (e.shiftKey) {
         actionHint.textContent = 'Open Folder';
@@ -90,19 +458,172 @@ function updateActionHint(e) {
 }
 
 searchInput.addEventListener('input', (e) => {
+    if (panel && panel.mode === 'input') return;
+    if (panel) {
+        filterPanel(e.target.value.trim());
+        return;
+    }
+    renderOperatorHints();
     clearTimeout(debounceTimer);
     debounceTimer = setTimeout(() => {
         search(e.target.value.trim());
     }, 100);
 });
 
+function leavePanelLevel() {
+    if (panel.mode === 'apps' || panel.mode === 'input') {
+        backToActions();
+    } else {
+        closeActionPanel();
+    }
+}
+
+function handlePanelKey(e) {
+    switch (e.key) {
+        case 'Escape':
+        case 'Tab':
+            e.preventDefault();
+            leavePanelLevel();
+            break;
+        case 'ArrowLeft':
+            if (searchInput.value === '' && panel.mode !== 'input') {
+                e.preventDefault();
+                leavePanelLevel();
+            }
+            break;
+        case 'ArrowDown':
+            e.preventDefault();
+            if (panel.mode !== 'input' && panel.filtered.length > 0) {
+                panel.index = (panel.index + 1) % panel.filtered.length;
+                renderPanel();
+            }
+            break;
+        case 'ArrowUp':
+            e.preventDefault();
+            if (panel.mode !== 'input' && panel.filtered.length > 0) {
+                panel.index = (panel.index - 1 + panel.filtered.length) % panel.filtered.length;
+                renderPanel();
+            }
+            break;
+        case 'Enter':
+            e.preventDefault();
+            executePanelAction(e.ctrlKey);
+            break;
+    }
+}
+
+function dmenuModifier(e) {
+    if (e.ctrlKey) return 'ctrl';
+    if (e.shiftKey) return 'shift';
+    if (e.altKey) return 'alt';
+    return '';
+}
+
+function submitDmenu(modifier) {
+    let lines;
+    if (marked.size > 0) {
+        lines = [...marked];
+    } else if (results.length > 0) {
+        lines = [results[selectedIndex].name];
+    } else if (searchInput.value) {
+        lines = [searchInput.value];
+    } else {
+        return;
+    }
+    window.ipc.postMessage(JSON.stringify({ type: 'dmenu_select', lines, modifier }));
+}
+
+function toggleMark(line) {
+    if (marked.has(line)) {
+        marked.delete(line);
+    } else {
+        marked.add(line);
+    }
+    selectedIndex = Math.min(selectedIndex + 1, results.length - 1);
+    renderResults();
+}
+
+function handleDmenuKey(e) {
+    switch (e.key) {
+        case 'Escape':
+            close();
+            break;
+        case 'Tab':
+            e.preventDefault();
+            if (dmenu.multi && results.length > 0) {
+                toggleMark(results[selectedIndex].name);
+            }
+            break;
+        case 'ArrowDown':
+            e.preventDefault();
+            if (results.length > 0) {
+                selectedIndex = (selectedIndex + 1) % results.length;
+                renderResults();
+            }
+            break;
+        case 'ArrowUp':
+            e.preventDefault();
+            if (results.length > 0) {
+                selectedIndex = (selectedIndex - 1 + results.length) % results.length;
+                renderResults();
+            }
+            break;
+        case 'Enter':
+            e.preventDefault();
+            submitDmenu(dmenuModifier(e));
+            break;
+    }
+}
+
 document.addEventListener('keydown', (e) => {
+    if (dmenu) {
+        handleDmenuKey(e);
+        return;
+    }
+    if (panel) {
+        handlePanelKey(e);
+        return;
+    }
+
     updateActionHint(e);
 
+    const custom = findCustomAction(e);
+    if (custom) {
+        e.preventDefault();
+        executeSelected(custom.name);
+        return;
+    }
+
+    if (e.ctrlKey && !e.shiftKey && !e.altKey && e.key.toLowerCase() === 'p' && profiles.length) {
+        e.preventDefault();
+        cycleProfile();
+        return;
+    }
+
+    if (e.ctrlKey && !e.shiftKey && !e.altKey && e.key.toLowerCase() === 'e') {
+        e.preventDefault();
+        explain = !explain;
+        renderResults();
+        return;
+    }
+
     switch (e.key) {
         case 'Escape':
             close();
             break;
+        case 'Tab':
+            e.preventDefault();
+            // Plain Tab always opens the actions; operators complete on Shift+Tab.
+            if (!(e.shiftKey && completeOperator())) {
+                openActionPanel();
+            }
+            break;
+        case 'ArrowRight':
+            if (searchInput.selectionStart === searchInput.value.length && results.length > 0) {
+                e.preventDefault();
+                openActionPanel();
+            }
+            break;
         case 'ArrowDown':
             e.preventDefault();
             if (results.length > 0) {
@@ -136,10 +657,17 @@ document.addEventListener('keyup', updateActionHint);
 
 resultsContainer.addEventListener('click', (e) => {
     const item = e.target.closest('.result-item');
-    if (item) {
+    if (item && dmenu) {
+        selectedIndex = parseInt(item.dataset.index, 10);
+        submitDmenu(dmenuModifier(e));
+    } else if (item && panel) {
+        panel.index = parseInt(item.dataset.index, 10);
+        executePanelAction(e.ctrlKey);
+    } else if (item) {
         selectedIndex = parseInt(item.dataset.index, 10);
         executeSelected('open');
     }
 });
 
+searchInput.placeholder = searchPlaceholder();
 renderResults();